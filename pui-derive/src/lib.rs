@@ -0,0 +1,265 @@
+//! Derive macros for [`pui`](https://docs.rs/pui)
+//!
+//! These are re-exported from `pui` with the `derive` feature, so you should use them
+//! through `pui::Identifier` and `pui::Handle`.
+
+#![warn(missing_docs)]
+#![warn(rust_2018_idioms)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Member, Result, Type};
+
+/// Implement `pui::Identifier` and `pui::SharedIdentifier` for a newtype around an identifier
+///
+/// The type must be a struct with exactly one field, and that field must be an identifier.
+/// Every method forwards to the field, so the newtype owns exactly the same handles as
+/// the field. Like every identifier, the newtype must implement `Eq`.
+///
+/// ```
+/// use pui::{runtime::Runtime, Identifier};
+///
+/// pui::make_global_id_alloc! {
+///     type DocAlloc(DocId) = u32;
+/// }
+///
+/// #[derive(Identifier, PartialEq, Eq)]
+/// struct DocOwner(Runtime<DocAlloc>);
+///
+/// let owner = DocOwner(DocAlloc::new());
+/// assert!(owner.owns(&owner.handle()));
+/// ```
+///
+/// By default the newtype uses the same handle as the field. You can use a handle newtype,
+/// created with [`derive(Handle)`](macro@Handle), with `#[identifier(handle = ...)]`
+///
+/// ```
+/// use pui::{runtime::{Runtime, RuntimeHandle}, Handle, Identifier};
+///
+/// pui::make_global_id_alloc! {
+///     type DocAlloc(DocId) = u32;
+/// }
+///
+/// #[derive(Handle, Copy)]
+/// struct DocHandle(RuntimeHandle<DocAlloc>);
+///
+/// #[derive(Identifier, PartialEq, Eq)]
+/// #[identifier(handle = DocHandle)]
+/// struct DocOwner(Runtime<DocAlloc>);
+///
+/// let owner = DocOwner(DocAlloc::new());
+/// let handle: DocHandle = owner.handle();
+/// assert!(owner.owns(&handle));
+/// ```
+///
+/// Fields that aren't identifiers are rejected
+///
+/// ```compile_fail
+/// #[derive(pui::Identifier, PartialEq, Eq)]
+/// struct NotAnOwner(u32);
+/// ```
+#[proc_macro_derive(Identifier, attributes(identifier))]
+pub fn derive_identifier(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_identifier(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implement `pui::Handle` for a newtype around a handle
+///
+/// The type must be a struct with exactly one field, and that field must be a handle.
+/// `Handle` requires `Clone` and `Eq` to agree with the field, so this also implements
+/// `Clone`, `PartialEq`, and `Eq` by forwarding to the field. Don't derive them yourself,
+/// other traits like `Copy`, `Debug`, or `Hash` can still be derived as usual.
+///
+/// ```
+/// use pui::{runtime::RuntimeHandle, Handle};
+///
+/// #[derive(Handle, Copy, Debug)]
+/// struct DocHandle(RuntimeHandle);
+/// ```
+///
+/// This also allows the newtype to be used as the handle of an identifier created with
+/// [`derive(Identifier)`](macro@Identifier), see the docs there for an example.
+///
+/// Fields that aren't handles are rejected
+///
+/// ```compile_fail
+/// #[derive(pui::Handle)]
+/// struct NotAHandle(u32);
+/// ```
+#[proc_macro_derive(Handle)]
+pub fn derive_handle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_handle(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn single_field<'a>(input: &'a DeriveInput, derive: &str) -> Result<(Member, &'a Type)> {
+    let message = format!("`derive({})` only supports structs with exactly one field", derive);
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(Error::new(Span::call_site(), message)),
+    };
+
+    let field = match fields {
+        Fields::Named(fields) if fields.named.len() == 1 => &fields.named[0],
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+        _ => return Err(Error::new(fields.span(), message)),
+    };
+
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(0.into()),
+    };
+
+    Ok((member, &field.ty))
+}
+
+fn handle_attr(input: &DeriveInput) -> Result<Option<Type>> {
+    let mut handle = None;
+
+    for attr in &input.attrs {
+        if !attr.path().is_ident("identifier") {
+            continue
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("handle") {
+                if handle.is_some() {
+                    return Err(meta.error("the handle was already specified"))
+                }
+
+                handle = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `handle = ...`"))
+            }
+        })?;
+    }
+
+    Ok(handle)
+}
+
+fn expand_identifier(input: DeriveInput) -> Result<TokenStream2> {
+    let (member, field) = single_field(&input, "Identifier")?;
+    let handle = handle_attr(&input)?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut predicates = where_clause
+        .map(|where_clause| where_clause.predicates.clone())
+        .unwrap_or_default();
+    predicates.push(syn::parse_quote!(#field: ::pui::Identifier));
+
+    let body = match handle {
+        None => quote! {
+            type Handle = <#field as ::pui::Identifier>::Handle;
+
+            #[inline]
+            fn handle(&self) -> Self::Handle { ::pui::Identifier::handle(&self.#member) }
+
+            #[inline]
+            fn owns(&self, handle: &Self::Handle) -> bool { ::pui::Identifier::owns(&self.#member, handle) }
+
+            #[inline]
+            fn check(&self, handle: &Self::Handle) -> ::core::result::Result<(), ::pui::OwnershipError> {
+                ::pui::Identifier::check(&self.#member, handle)
+            }
+        },
+        Some(handle) => {
+            predicates.push(syn::parse_quote!(
+                #handle: ::pui::macros::HandleNewtype<Inner = <#field as ::pui::Identifier>::Handle>
+            ));
+
+            quote! {
+                type Handle = #handle;
+
+                #[inline]
+                fn handle(&self) -> Self::Handle {
+                    ::pui::macros::HandleNewtype::wrap(::pui::Identifier::handle(&self.#member))
+                }
+
+                #[inline]
+                fn owns(&self, handle: &Self::Handle) -> bool {
+                    ::pui::Identifier::owns(&self.#member, ::pui::macros::HandleNewtype::inner(handle))
+                }
+
+                #[inline]
+                fn check(&self, handle: &Self::Handle) -> ::core::result::Result<(), ::pui::OwnershipError> {
+                    ::pui::Identifier::check(&self.#member, ::pui::macros::HandleNewtype::inner(handle))
+                }
+            }
+        }
+    };
+
+    Ok(quote! {
+        // This is safe because every method forwards to an identifier,
+        // so `#name` owns exactly the same handles as its field
+        unsafe impl #impl_generics ::pui::Identifier for #name #ty_generics where #predicates {
+            #body
+        }
+
+        // This is safe because `#name` is an identifier, see `SharedIdentifier`
+        unsafe impl #impl_generics ::pui::SharedIdentifier for #name #ty_generics where #predicates {
+            type Handle = <Self as ::pui::Identifier>::Handle;
+
+            #[inline]
+            fn owns_shared(&self, handle: &Self::Handle) -> bool { ::pui::Identifier::owns(self, handle) }
+
+            #[inline]
+            fn check_shared(&self, handle: &Self::Handle) -> ::core::result::Result<(), ::pui::OwnershipError> {
+                ::pui::Identifier::check(self, handle)
+            }
+        }
+    })
+}
+
+fn expand_handle(input: DeriveInput) -> Result<TokenStream2> {
+    let (member, field) = single_field(&input, "Handle")?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut predicates = where_clause
+        .map(|where_clause| where_clause.predicates.clone())
+        .unwrap_or_default();
+    predicates.push(syn::parse_quote!(#field: ::pui::Handle));
+
+    let construct = match &member {
+        Member::Named(ident) => quote!(Self { #ident: inner }),
+        Member::Unnamed(_) => quote!(Self(inner)),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::clone::Clone for #name #ty_generics where #predicates {
+            #[inline]
+            fn clone(&self) -> Self {
+                let inner = ::core::clone::Clone::clone(&self.#member);
+                #construct
+            }
+        }
+
+        impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics where #predicates {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool { self.#member == other.#member }
+        }
+
+        impl #impl_generics ::core::cmp::Eq for #name #ty_generics where #predicates {}
+
+        // This is safe because `#name` only contains a handle, and the impls of
+        // `Clone` and `Eq` above forward to it
+        unsafe impl #impl_generics ::pui::Handle for #name #ty_generics where #predicates {}
+
+        unsafe impl #impl_generics ::pui::macros::HandleNewtype for #name #ty_generics where #predicates {
+            type Inner = #field;
+
+            #[inline]
+            fn wrap(inner: Self::Inner) -> Self { #construct }
+
+            #[inline]
+            fn inner(&self) -> &Self::Inner { &self.#member }
+        }
+    })
+}
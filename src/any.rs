@@ -0,0 +1,171 @@
+//! Type erased handles, for registries that hold handles of different identifiers
+//!
+//! ```
+//! use pui::{
+//!     any::{AnyHandle, AnyIdentifier},
+//!     runtime::{Global, Runtime, RuntimeHandle},
+//! };
+//! use std::collections::HashSet;
+//!
+//! pui::make_global_id_alloc! {
+//!     type MyIdAlloc(MyId) = u32;
+//! }
+//!
+//! let global = Runtime::new();
+//! let mine = MyIdAlloc::new();
+//!
+//! let registry: HashSet<AnyHandle> = [global.any_handle(), mine.any_handle()].iter().copied().collect();
+//!
+//! for handle in &registry {
+//!     assert!(AnyIdentifier::owns(&global, handle) ^ AnyIdentifier::owns(&mine, handle));
+//! }
+//!
+//! let handle = AnyHandle::new(global.handle());
+//! assert_eq!(handle.downcast::<RuntimeHandle<Global>>(), Some(global.handle()));
+//! assert_eq!(handle.downcast::<RuntimeHandle<MyIdAlloc>>(), None);
+//! ```
+
+use core::{
+    any::{type_name, TypeId},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    mem::size_of,
+};
+
+use crate::{Handle, Identifier};
+
+const SIZE: usize = 16;
+
+/// A type that can be stored in an [`AnyHandle`]
+///
+/// # Safety
+///
+/// * `Self` must be at most 16 bytes large
+/// * `Self` may not contain any padding bytes, pointers, or interior mutability
+/// * Two values of `Self` must compare equal (using `Eq`) if and only if their bytes are equal
+pub unsafe trait Erasable: Copy + Eq + Send + Sync + 'static {}
+
+/// A type erased handle
+///
+/// This stores the `TypeId` of the handle along with its bytes, and two `AnyHandle`s
+/// are equal if they hold equal handles of the same type. The ordering between
+/// handles of different types is unspecified, but consistent for the duration of the program.
+#[derive(Clone, Copy)]
+pub struct AnyHandle {
+    type_id: TypeId,
+    type_name: &'static str,
+    bytes: [u8; SIZE],
+}
+
+impl AnyHandle {
+    /// Erase the type of the given handle
+    pub fn new<H: Handle + Erasable>(handle: H) -> Self {
+        assert!(
+            size_of::<H>() <= SIZE,
+            "tried to erase a handle that is larger than 16 bytes"
+        );
+
+        let mut bytes = [0; SIZE];
+        // This is safe because `H: Erasable`, so all of its bytes are initialized,
+        // and it fits in `bytes`
+        unsafe { bytes.as_mut_ptr().cast::<H>().write_unaligned(handle) }
+
+        Self {
+            type_id: TypeId::of::<H>(),
+            type_name: type_name::<H>(),
+            bytes,
+        }
+    }
+
+    /// Check if this holds a handle of type `H`
+    #[inline]
+    pub fn is<H: Handle + Erasable>(&self) -> bool { self.type_id == TypeId::of::<H>() }
+
+    /// Get back the handle, if it is of type `H`
+    pub fn downcast<H: Handle + Erasable>(&self) -> Option<H> {
+        if self.is::<H>() {
+            // This is safe because `self.bytes` was created from an `H` in `AnyHandle::new`
+            Some(unsafe { self.bytes.as_ptr().cast::<H>().read_unaligned() })
+        } else {
+            None
+        }
+    }
+
+    /// The `TypeId` of the erased handle
+    #[inline]
+    pub fn type_id(&self) -> TypeId { self.type_id }
+
+    /// The type name of the erased handle
+    #[inline]
+    pub fn type_name(&self) -> &'static str { self.type_name }
+}
+
+unsafe impl Handle for AnyHandle {}
+
+impl Eq for AnyHandle {}
+impl PartialEq for AnyHandle {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.type_id == other.type_id && self.bytes == other.bytes }
+}
+
+impl PartialOrd for AnyHandle {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for AnyHandle {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.type_id
+            .cmp(&other.type_id)
+            .then_with(|| self.bytes.cmp(&other.bytes))
+    }
+}
+
+impl Hash for AnyHandle {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_id.hash(state);
+        self.bytes.hash(state);
+    }
+}
+
+impl fmt::Debug for AnyHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyHandle")
+            .field("type", &self.type_name)
+            .field("bytes", &&self.bytes[..])
+            .finish()
+    }
+}
+
+/// An identifier that can check if it owns an [`AnyHandle`]
+///
+/// This is implemented for every [`Identifier`] whose handle is [`Erasable`].
+/// Because `Identifier` also has an `owns` method, you may need to call this as
+/// `AnyIdentifier::owns(&ident, &handle)`
+pub trait AnyIdentifier {
+    /// Create a type erased handle that this identifier owns
+    fn any_handle(&self) -> AnyHandle;
+
+    /// Check if the current identifier owns the given handle,
+    /// this is false if the handle has a different type than this identifier's handle
+    fn owns(&self, handle: &AnyHandle) -> bool;
+}
+
+impl<I: ?Sized + Identifier> AnyIdentifier for I
+where
+    I::Handle: Erasable,
+{
+    #[inline]
+    fn any_handle(&self) -> AnyHandle { AnyHandle::new(self.handle()) }
+
+    #[inline]
+    fn owns(&self, handle: &AnyHandle) -> bool {
+        match handle.downcast::<I::Handle>() {
+            Some(handle) => Identifier::owns(self, &handle),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Cells that are owned by an [`Identifier`](crate::Identifier)
+//!
+//! An [`IdCell`] stores a handle next to its value. Only the identifier that owns
+//! the handle can access the value, a shared reference to the identifier gives
+//! shared access to the value, and a unique reference to the identifier gives unique
+//! access. This way a single identifier can guard any number of cells, no matter where
+//! they are stored.
+//!
+//! ```
+//! use pui::{cell::IdCell, runtime::Runtime};
+//!
+//! let mut owner = Runtime::new();
+//!
+//! let a = IdCell::new(owner.handle(), 1);
+//! let b = IdCell::new(owner.handle(), 2);
+//!
+//! *a.borrow_mut(&mut owner) += 10;
+//! *b.borrow_mut(&mut owner) += 20;
+//!
+//! assert_eq!(*a.borrow(&owner) + *b.borrow(&owner), 33);
+//! ```
+//!
+//! If you only need shared access, you can share the identifier with `Rc` or `Arc`,
+//! see [`SharedIdentifier`](crate::SharedIdentifier). To get unique access from many threads,
+//! put the identifier in one of the locks in [`lock`](crate::lock). Or, if you only need atomics,
+//! use [`IdAtomicCell`], which only needs a shared reference to the identifier.
+//!
+//! ### `Send` and `Sync`
+//!
+//! * `IdCell<H, T>: Send` if `H: Send` and `T: Send`
+//! * `IdCell<H, T>: Sync` if `H: Send + Sync` and `T: Send + Sync`
+//!
+//! `T: Send` is required for `Sync`, because a thread that can see the cell
+//! can uniquely borrow its value with the identifier, and swap it out.
+//!
+//! Thread local handles, like the ones created by [`typeid_tl`](crate::typeid_tl)
+//! and [`make_global_id_alloc_tl`](crate::make_global_id_alloc_tl), are neither `Send`
+//! nor `Sync`, so cells branded by them can never leave the thread they were created on.
+//! This is important, because thread local identifiers are only unique on a single thread.
+
+use core::{cell::UnsafeCell, fmt, mem};
+
+use crate::{Identifier, SharedIdentifier};
+
+mod atomic;
+
+pub use self::atomic::*;
+
+/// A cell that can only be accessed through the [`Identifier`](crate::Identifier)
+/// that owns its handle
+///
+/// see module docs for details
+pub struct IdCell<H, T: ?Sized> {
+    handle: H,
+    value: UnsafeCell<T>,
+}
+
+// This is safe because the value can only be accessed through the identifier that owns `handle`,
+// and if the handle is `Send + Sync`, then there can only be one such identifier in the whole
+// process (see the safety docs on `Identifier`). So the identifier synchronizes all accesses to
+// the value, just like a `RwLock` would.
+unsafe impl<H: Send + Sync, T: ?Sized + Send + Sync> Sync for IdCell<H, T> {}
+
+impl<H, T> IdCell<H, T> {
+    /// Create a new cell that is owned by whichever identifier owns `handle`
+    #[inline]
+    pub const fn new(handle: H, value: T) -> Self {
+        Self {
+            handle,
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Get the underlying value
+    #[inline]
+    pub fn into_inner(self) -> T { self.value.into_inner() }
+}
+
+impl<H, T: ?Sized> IdCell<H, T> {
+    /// The handle that brands this cell
+    #[inline]
+    pub fn handle(&self) -> &H { &self.handle }
+
+    /// Get a mutable reference to the underlying value
+    ///
+    /// This doesn't need the identifier, because `&mut self` guarantees unique access
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T { self.value.get_mut() }
+
+    /// Get a raw pointer to the underlying value
+    #[inline]
+    pub const fn as_ptr(&self) -> *mut T { self.value.get() }
+
+    /// Check if the given identifier owns this cell
+    #[inline]
+    pub fn is_owned_by<I: SharedIdentifier<Handle = H>>(&self, ident: &I) -> bool { ident.owns_shared(&self.handle) }
+
+    /// Get a shared reference to the value, if `ident` owns this cell
+    ///
+    /// This only needs a [`SharedIdentifier`], so you can also use an `Rc<I>` or `Arc<I>`
+    pub fn try_borrow<'a, I: SharedIdentifier<Handle = H>>(&'a self, ident: &'a I) -> Option<&'a T> {
+        if ident.owns_shared(&self.handle) {
+            // This is safe because `ident` owns the cell, and we have shared access
+            // to `ident` for as long as the returned reference lives, so no one can
+            // get unique access to the value at the same time (see `SharedIdentifier`)
+            Some(unsafe { &*self.value.get() })
+        } else {
+            None
+        }
+    }
+
+    /// Get a unique reference to the value, if `ident` owns this cell
+    pub fn try_borrow_mut<'a, I: Identifier<Handle = H>>(&'a self, ident: &'a mut I) -> Option<&'a mut T> {
+        if ident.owns(&self.handle) {
+            // This is safe because `ident` owns the cell, and we have unique access
+            // to `ident` for as long as the returned reference lives, so no one else
+            // can access the value at the same time
+            Some(unsafe { &mut *self.value.get() })
+        } else {
+            None
+        }
+    }
+
+    /// Get a shared reference to the value
+    ///
+    /// This only needs a [`SharedIdentifier`], so you can also use an `Rc<I>` or `Arc<I>`
+    ///
+    /// # Panic
+    ///
+    /// If `ident` doesn't own this cell
+    pub fn borrow<'a, I: SharedIdentifier<Handle = H>>(&'a self, ident: &'a I) -> &'a T {
+        crate::assert_owns!(
+            *ident,
+            self.handle,
+            "tried to access a cell that is not owned by this identifier"
+        );
+        // This is safe because `ident` owns the cell, see `try_borrow`
+        unsafe { &*self.value.get() }
+    }
+
+    /// Get a unique reference to the value
+    ///
+    /// # Panic
+    ///
+    /// If `ident` doesn't own this cell
+    pub fn borrow_mut<'a, I: Identifier<Handle = H>>(&'a self, ident: &'a mut I) -> &'a mut T {
+        crate::assert_owns!(
+            *ident,
+            self.handle,
+            "tried to access a cell that is not owned by this identifier"
+        );
+        // This is safe because `ident` owns the cell, see `try_borrow_mut`
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Get unique references to the values of two different cells at once
+    ///
+    /// # Panic
+    ///
+    /// If `ident` doesn't own both cells, or if the values of the cells overlap
+    /// (for example, if they are the same cell)
+    pub fn borrow_mut2<'a, U: ?Sized, I: Identifier<Handle = H>>(
+        &'a self,
+        other: &'a IdCell<H, U>,
+        ident: &'a mut I,
+    ) -> (&'a mut T, &'a mut U) {
+        crate::assert_owns!(
+            *ident,
+            self.handle,
+            "tried to access a cell that is not owned by this identifier"
+        );
+        crate::assert_owns!(
+            *ident,
+            other.handle,
+            "tried to access a cell that is not owned by this identifier"
+        );
+
+        // `other` could be stored inside of `self`'s value, so check that
+        // the values don't overlap instead of just checking their addresses
+        let a = self.value.get() as *const u8 as usize;
+        let b = other.value.get() as *const u8 as usize;
+        // This is safe because we have shared access to both values
+        let (a_len, b_len) = unsafe {
+            (
+                mem::size_of_val(&*self.value.get()),
+                mem::size_of_val(&*other.value.get()),
+            )
+        };
+
+        assert!(
+            a + a_len <= b || b + b_len <= a,
+            "tried to uniquely borrow overlapping cells"
+        );
+
+        // This is safe because `ident` owns both cells, we have unique access to `ident`
+        // for as long as the returned references live, and the values don't overlap
+        unsafe { (&mut *self.value.get(), &mut *other.value.get()) }
+    }
+}
+
+impl<H: fmt::Debug, T: ?Sized> fmt::Debug for IdCell<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdCell")
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
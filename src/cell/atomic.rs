@@ -0,0 +1,217 @@
+use core::{fmt, sync::atomic::*};
+
+use crate::SharedIdentifier;
+
+/// An atomic that can only be accessed through the [`Identifier`](crate::Identifier)
+/// that owns its handle
+///
+/// Unlike [`IdCell`](super::IdCell), every operation only needs a
+/// [`SharedIdentifier`](crate::SharedIdentifier), so threads that share an identifier
+/// (for example through an `Arc`) can coordinate through these cells without locking,
+/// while anyone else can't touch them.
+///
+/// ```
+/// use pui::{cell::IdAtomicU32, runtime::Runtime};
+/// use std::sync::{atomic::Ordering, Arc};
+///
+/// let owner = Arc::new(Runtime::new());
+/// let counter = Arc::new(IdAtomicU32::new(owner.handle(), 0));
+///
+/// let threads: Vec<_> = (0..4)
+///     .map(|_| {
+///         let owner = owner.clone();
+///         let counter = counter.clone();
+///         std::thread::spawn(move || {
+///             counter.fetch_add(&*owner, 1, Ordering::Relaxed);
+///         })
+///     })
+///     .collect();
+///
+/// for thread in threads {
+///     thread.join().unwrap();
+/// }
+///
+/// assert_eq!(counter.load(&*owner, Ordering::Relaxed), 4);
+/// ```
+pub struct IdAtomicCell<H, A> {
+    handle: H,
+    atomic: A,
+}
+
+impl<H, A> IdAtomicCell<H, A> {
+    /// Create a new cell from an existing atomic, that is owned by whichever identifier owns `handle`
+    #[inline]
+    pub const fn from_atomic(handle: H, atomic: A) -> Self { Self { handle, atomic } }
+
+    /// The handle that brands this cell
+    #[inline]
+    pub fn handle(&self) -> &H { &self.handle }
+
+    /// Get the underlying atomic
+    ///
+    /// This doesn't need the identifier, because `&mut self` guarantees unique access
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut A { &mut self.atomic }
+
+    /// Get the underlying atomic
+    #[inline]
+    pub fn into_atomic(self) -> A { self.atomic }
+
+    /// Check if the given identifier owns this cell
+    #[inline]
+    pub fn is_owned_by<I: SharedIdentifier<Handle = H>>(&self, ident: &I) -> bool { ident.owns_shared(&self.handle) }
+
+    /// Get the underlying atomic, if `ident` owns this cell
+    pub fn try_atomic<'a, I: SharedIdentifier<Handle = H>>(&'a self, ident: &'a I) -> Option<&'a A> {
+        if ident.owns_shared(&self.handle) {
+            Some(&self.atomic)
+        } else {
+            None
+        }
+    }
+
+    /// Get the underlying atomic
+    ///
+    /// # Panic
+    ///
+    /// If `ident` doesn't own this cell
+    pub fn atomic<'a, I: SharedIdentifier<Handle = H>>(&'a self, ident: &'a I) -> &'a A {
+        crate::assert_owns!(
+            *ident,
+            self.handle,
+            "tried to access a cell that is not owned by this identifier"
+        );
+        &self.atomic
+    }
+}
+
+impl<H: fmt::Debug, A> fmt::Debug for IdAtomicCell<H, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdAtomicCell")
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
+
+macro_rules! common {
+    ($alias:ident, $atomic:ident, $value:ty, $cfg:literal) => {
+        #[doc = concat!("An [`IdAtomicCell`] that holds a [`", stringify!($atomic), "`]")]
+        #[cfg_attr(feature = "nightly", cfg(target_has_atomic = $cfg))]
+        pub type $alias<H> = IdAtomicCell<H, $atomic>;
+
+        #[cfg_attr(feature = "nightly", cfg(target_has_atomic = $cfg))]
+        impl<H> IdAtomicCell<H, $atomic> {
+            /// Create a new cell that is owned by whichever identifier owns `handle`
+            #[inline]
+            pub const fn new(handle: H, value: $value) -> Self { Self::from_atomic(handle, $atomic::new(value)) }
+
+            /// Get the underlying value
+            #[inline]
+            pub fn into_inner(self) -> $value { self.atomic.into_inner() }
+
+            #[doc = concat!("Loads the value, see [`", stringify!($atomic), "::load`] for details")]
+            ///
+            /// # Panic
+            ///
+            /// If `ident` doesn't own this cell
+            #[inline]
+            pub fn load<I: SharedIdentifier<Handle = H>>(&self, ident: &I, order: Ordering) -> $value {
+                self.atomic(ident).load(order)
+            }
+
+            #[doc = concat!("Stores the value, see [`", stringify!($atomic), "::store`] for details")]
+            ///
+            /// # Panic
+            ///
+            /// If `ident` doesn't own this cell
+            #[inline]
+            pub fn store<I: SharedIdentifier<Handle = H>>(&self, ident: &I, value: $value, order: Ordering) {
+                self.atomic(ident).store(value, order)
+            }
+
+            #[doc = concat!("Stores the value and returns the old value, see [`", stringify!($atomic), "::swap`] for details")]
+            ///
+            /// # Panic
+            ///
+            /// If `ident` doesn't own this cell
+            #[inline]
+            pub fn swap<I: SharedIdentifier<Handle = H>>(&self, ident: &I, value: $value, order: Ordering) -> $value {
+                self.atomic(ident).swap(value, order)
+            }
+
+            #[doc = concat!("Stores `new` if the value is `current`, see [`", stringify!($atomic), "::compare_exchange`] for details")]
+            ///
+            /// # Panic
+            ///
+            /// If `ident` doesn't own this cell
+            #[inline]
+            pub fn compare_exchange<I: SharedIdentifier<Handle = H>>(
+                &self,
+                ident: &I,
+                current: $value,
+                new: $value,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$value, $value> {
+                self.atomic(ident).compare_exchange(current, new, success, failure)
+            }
+
+            #[doc = concat!("Updates the value with `f` until it succeeds, see [`", stringify!($atomic), "::fetch_update`] for details")]
+            ///
+            /// # Panic
+            ///
+            /// If `ident` doesn't own this cell
+            #[inline]
+            pub fn fetch_update<I: SharedIdentifier<Handle = H>, F: FnMut($value) -> Option<$value>>(
+                &self,
+                ident: &I,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                f: F,
+            ) -> Result<$value, $value> {
+                self.atomic(ident).fetch_update(set_order, fetch_order, f)
+            }
+        }
+    };
+}
+
+macro_rules! fetch_ops {
+    ($atomic:ident, $value:ty, $cfg:literal, $($op:ident)*) => {
+        #[cfg_attr(feature = "nightly", cfg(target_has_atomic = $cfg))]
+        impl<H> IdAtomicCell<H, $atomic> {$(
+            #[doc = concat!("see [`", stringify!($atomic), "::", stringify!($op), "`] for details")]
+            ///
+            /// # Panic
+            ///
+            /// If `ident` doesn't own this cell
+            #[inline]
+            pub fn $op<I: SharedIdentifier<Handle = H>>(&self, ident: &I, value: $value, order: Ordering) -> $value {
+                self.atomic(ident).$op(value, order)
+            }
+        )*}
+    };
+}
+
+macro_rules! int {
+    ($(($alias:ident, $atomic:ident, $value:ty, $cfg:literal))*) => {$(
+        common!($alias, $atomic, $value, $cfg);
+        fetch_ops!($atomic, $value, $cfg, fetch_add fetch_sub fetch_and fetch_nand fetch_or fetch_xor fetch_max fetch_min);
+    )*};
+}
+
+common!(IdAtomicBool, AtomicBool, bool, "8");
+fetch_ops!(AtomicBool, bool, "8", fetch_and fetch_nand fetch_or fetch_xor);
+
+int! {
+    (IdAtomicU8, AtomicU8, u8, "8")
+    (IdAtomicU16, AtomicU16, u16, "16")
+    (IdAtomicU32, AtomicU32, u32, "32")
+    (IdAtomicU64, AtomicU64, u64, "64")
+    (IdAtomicUsize, AtomicUsize, usize, "ptr")
+
+    (IdAtomicI8, AtomicI8, i8, "8")
+    (IdAtomicI16, AtomicI16, i16, "16")
+    (IdAtomicI32, AtomicI32, i32, "32")
+    (IdAtomicI64, AtomicI64, i64, "64")
+    (IdAtomicIsize, AtomicIsize, isize, "ptr")
+}
@@ -0,0 +1,397 @@
+//! Branded 2D grids
+//!
+//! A [`BrandedGrid`] takes ownership of a [`OneShotIdentifier`](crate::OneShotIdentifier),
+//! and uses it to brand [`Row`]s, [`Col`]s, and [`Coord`]s. These are checked to be in
+//! bounds once when they are created, after that they can be used to index into the
+//! grid without any bounds checks. If the identifier's handle is [`Trivial`](crate::Trivial),
+//! like [`ScopedHandle`](crate::scoped::ScopedHandle), then the ownership check is a no-op too.
+//!
+//! ```
+//! use pui::{grid::BrandedGrid, scoped::Scoped};
+//!
+//! Scoped::with(|ident| {
+//!     let mut grid = BrandedGrid::from_fn(ident, 3, 4, |row, col| row * 4 + col);
+//!
+//!     let coord = grid.coord(1, 2).unwrap();
+//!     assert_eq!(grid[coord], 6);
+//!
+//!     let neighbours: Vec<_> = grid.neighbours4(coord).map(|coord| grid[coord]).collect();
+//!     assert_eq!(neighbours, [2, 10, 5, 7]);
+//!
+//!     for coord in grid.neighbours4(grid.coord(0, 0).unwrap()) {
+//!         grid[coord] = 0;
+//!     }
+//!
+//!     assert_eq!(grid[grid.row(0).unwrap()], [0, 0, 2, 3]);
+//! })
+//! ```
+
+use core::{fmt, ops};
+use std::vec::Vec;
+
+use crate::OneShotIdentifier;
+
+/// A 2D grid that is branded by a [`OneShotIdentifier`](crate::OneShotIdentifier)
+///
+/// The elements are stored in row-major order
+///
+/// see module docs for details
+pub struct BrandedGrid<I, T> {
+    ident: I,
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+/// A row that is guaranteed to be in bounds of the [`BrandedGrid`]
+/// whose identifier owns `handle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Row<H> {
+    index: usize,
+    handle: H,
+}
+
+/// A column that is guaranteed to be in bounds of the [`BrandedGrid`]
+/// whose identifier owns `handle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Col<H> {
+    index: usize,
+    handle: H,
+}
+
+/// A coordinate that is guaranteed to be in bounds of the [`BrandedGrid`]
+/// whose identifier owns `handle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coord<H> {
+    row: usize,
+    col: usize,
+    handle: H,
+}
+
+impl<H> Row<H> {
+    /// The underlying row index
+    #[inline]
+    pub const fn get(&self) -> usize { self.index }
+
+    /// The handle that brands this row
+    #[inline]
+    pub const fn handle(&self) -> &H { &self.handle }
+}
+
+impl<H> Col<H> {
+    /// The underlying column index
+    #[inline]
+    pub const fn get(&self) -> usize { self.index }
+
+    /// The handle that brands this column
+    #[inline]
+    pub const fn handle(&self) -> &H { &self.handle }
+}
+
+impl<H> Coord<H> {
+    /// The underlying row and column indices
+    #[inline]
+    pub const fn get(&self) -> (usize, usize) { (self.row, self.col) }
+
+    /// The handle that brands this coordinate
+    #[inline]
+    pub const fn handle(&self) -> &H { &self.handle }
+}
+
+impl<H: Clone> Coord<H> {
+    /// The row of this coordinate
+    #[inline]
+    pub fn row(&self) -> Row<H> {
+        Row {
+            index: self.row,
+            handle: self.handle.clone(),
+        }
+    }
+
+    /// The column of this coordinate
+    #[inline]
+    pub fn col(&self) -> Col<H> {
+        Col {
+            index: self.col,
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<I: OneShotIdentifier, T> BrandedGrid<I, T> {
+    /// Create a new grid with `rows` rows and `cols` columns from row-major data
+    ///
+    /// returns `None` if `data` doesn't have exactly `rows * cols` elements
+    pub fn from_vec(ident: I, rows: usize, cols: usize, data: Vec<T>) -> Option<Self> {
+        if rows.checked_mul(cols)? == data.len() {
+            Some(Self {
+                ident,
+                rows,
+                cols,
+                data,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Create a new grid with `rows` rows and `cols` columns, where each
+    /// element is initialized by calling `f(row, col)`
+    ///
+    /// # Panic
+    ///
+    /// If `rows * cols` overflows
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(ident: I, rows: usize, cols: usize, mut f: F) -> Self {
+        let len = rows.checked_mul(cols).expect("grid size overflowed");
+        let mut data = Vec::with_capacity(len);
+
+        for row in 0..rows {
+            for col in 0..cols {
+                data.push(f(row, col));
+            }
+        }
+
+        Self {
+            ident,
+            rows,
+            cols,
+            data,
+        }
+    }
+
+    /// The identifier that brands this grid
+    #[inline]
+    pub fn identifier(&self) -> &I { &self.ident }
+
+    /// The number of rows in the grid
+    #[inline]
+    pub fn height(&self) -> usize { self.rows }
+
+    /// The number of columns in the grid
+    #[inline]
+    pub fn width(&self) -> usize { self.cols }
+
+    /// The elements of the grid, in row-major order
+    #[inline]
+    pub fn as_slice(&self) -> &[T] { &self.data }
+
+    /// The elements of the grid, in row-major order
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] { &mut self.data }
+
+    /// Get the elements of the grid, in row-major order
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> { self.data }
+
+    /// Check if `row` is in bounds, and if it is brand it
+    pub fn row(&self, row: usize) -> Option<Row<I::Handle>> {
+        if row < self.rows {
+            Some(Row {
+                index: row,
+                handle: self.ident.handle(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Check if `col` is in bounds, and if it is brand it
+    pub fn col(&self, col: usize) -> Option<Col<I::Handle>> {
+        if col < self.cols {
+            Some(Col {
+                index: col,
+                handle: self.ident.handle(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Check if `(row, col)` is in bounds, and if it is brand it
+    pub fn coord(&self, row: usize, col: usize) -> Option<Coord<I::Handle>> {
+        if row < self.rows && col < self.cols {
+            Some(Coord {
+                row,
+                col,
+                handle: self.ident.handle(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Combine a row and a column into a coordinate
+    ///
+    /// # Panic
+    ///
+    /// If either the row or the column is not owned by this grid
+    pub fn at(&self, row: Row<I::Handle>, col: Col<I::Handle>) -> Coord<I::Handle> {
+        assert!(
+            self.ident.owns(&row.handle) && self.ident.owns(&col.handle),
+            "tried to use a row or column that is not owned by this grid"
+        );
+
+        Coord {
+            row: row.index,
+            col: col.index,
+            handle: row.handle,
+        }
+    }
+
+    /// An iterator over all rows of the grid
+    pub fn rows(&self) -> impl Iterator<Item = Row<I::Handle>> {
+        let handle = self.ident.handle();
+        (0..self.rows).map(move |index| Row {
+            index,
+            handle: handle.clone(),
+        })
+    }
+
+    /// An iterator over all columns of the grid
+    pub fn cols(&self) -> impl Iterator<Item = Col<I::Handle>> {
+        let handle = self.ident.handle();
+        (0..self.cols).map(move |index| Col {
+            index,
+            handle: handle.clone(),
+        })
+    }
+
+    /// An iterator over all coordinates of the grid, in row-major order
+    pub fn coords(&self) -> impl Iterator<Item = Coord<I::Handle>> {
+        let handle = self.ident.handle();
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |row| {
+            let handle = handle.clone();
+            (0..cols).map(move |col| Coord {
+                row,
+                col,
+                handle: handle.clone(),
+            })
+        })
+    }
+
+    fn neighbours(
+        &self,
+        coord: Coord<I::Handle>,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Coord<I::Handle>> {
+        assert!(
+            self.ident.owns(&coord.handle),
+            "tried to use a coordinate that is not owned by this grid"
+        );
+
+        let (rows, cols) = (self.rows, self.cols);
+
+        // negative offsets wrap around to very large values, which are then out of bounds
+        offsets.iter().filter_map(move |&(row_offset, col_offset)| {
+            let row = coord.row.wrapping_add(row_offset as usize);
+            let col = coord.col.wrapping_add(col_offset as usize);
+
+            if row < rows && col < cols {
+                Some(Coord {
+                    row,
+                    col,
+                    handle: coord.handle.clone(),
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    /// An iterator over the orthogonal neighbours of `coord` which are in bounds
+    ///
+    /// The neighbours are yielded in the order: up, down, left, right
+    ///
+    /// # Panic
+    ///
+    /// If the coordinate is not owned by this grid
+    pub fn neighbours4(&self, coord: Coord<I::Handle>) -> impl Iterator<Item = Coord<I::Handle>> {
+        self.neighbours(coord, &[(-1, 0), (1, 0), (0, -1), (0, 1)])
+    }
+
+    /// An iterator over the orthogonal and diagonal neighbours of `coord` which are in bounds
+    ///
+    /// The neighbours are yielded in row-major order
+    ///
+    /// # Panic
+    ///
+    /// If the coordinate is not owned by this grid
+    pub fn neighbours8(&self, coord: Coord<I::Handle>) -> impl Iterator<Item = Coord<I::Handle>> {
+        self.neighbours(coord, &[
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ])
+    }
+
+    fn offset(&self, coord: &Coord<I::Handle>) -> usize {
+        assert!(
+            self.ident.owns(&coord.handle),
+            "tried to use a coordinate that is not owned by this grid"
+        );
+
+        coord.row * self.cols + coord.col
+    }
+
+    fn row_range(&self, row: &Row<I::Handle>) -> ops::Range<usize> {
+        assert!(
+            self.ident.owns(&row.handle),
+            "tried to use a row that is not owned by this grid"
+        );
+
+        let start = row.index * self.cols;
+        start..start + self.cols
+    }
+}
+
+// These are all safe because `ident` owns the `Row`/`Coord`, and it is one-shot, so
+// it was created by this grid, which means that it is in bounds. The size of the grid never changes
+// after it is created.
+
+impl<I: OneShotIdentifier, T> ops::Index<Coord<I::Handle>> for BrandedGrid<I, T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord<I::Handle>) -> &Self::Output {
+        let offset = self.offset(&coord);
+        unsafe { self.data.get_unchecked(offset) }
+    }
+}
+
+impl<I: OneShotIdentifier, T> ops::IndexMut<Coord<I::Handle>> for BrandedGrid<I, T> {
+    fn index_mut(&mut self, coord: Coord<I::Handle>) -> &mut Self::Output {
+        let offset = self.offset(&coord);
+        unsafe { self.data.get_unchecked_mut(offset) }
+    }
+}
+
+impl<I: OneShotIdentifier, T> ops::Index<Row<I::Handle>> for BrandedGrid<I, T> {
+    type Output = [T];
+
+    fn index(&self, row: Row<I::Handle>) -> &Self::Output {
+        let range = self.row_range(&row);
+        unsafe { self.data.get_unchecked(range) }
+    }
+}
+
+impl<I: OneShotIdentifier, T> ops::IndexMut<Row<I::Handle>> for BrandedGrid<I, T> {
+    fn index_mut(&mut self, row: Row<I::Handle>) -> &mut Self::Output {
+        let range = self.row_range(&row);
+        unsafe { self.data.get_unchecked_mut(range) }
+    }
+}
+
+impl<I, T: fmt::Debug> fmt::Debug for BrandedGrid<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cols == 0 {
+            return f.debug_list().finish()
+        }
+
+        f.debug_list().entries(self.data.chunks(self.cols)).finish()
+    }
+}
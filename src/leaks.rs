@@ -0,0 +1,216 @@
+//! Find identifiers that are still held, for example because they were leaked
+//!
+//! With the `track-leaks` feature, every [`Runtime`](crate::runtime::Runtime) and every
+//! [`Type`](crate::typeid::Type) created by the reusable form of [`make_typeid`](crate::make_typeid)
+//! records the thread that created it, and a [`Backtrace`] if backtraces are enabled
+//! (see [`Backtrace::capture`]). The record is removed when the identifier is dropped,
+//! so anything that is still recorded when you don't expect it, was leaked or is stuck
+//! on another thread.
+//!
+//! The owners created by [`thread_owner`](crate::thread_owner) aren't recorded, because
+//! they are held until their thread exits, so they would never pass [`assert_none_held`].
+//!
+//! ```
+//! pui::make_typeid! {
+//!     type MyType;
+//! }
+//!
+//! let ty = MyType::new();
+//! assert!(pui::leaks::held().iter().any(|held| held.type_name().contains("MyType")));
+//!
+//! drop(ty);
+//! assert!(!pui::leaks::held().iter().any(|held| held.type_name().contains("MyType")));
+//! ```
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+};
+use std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    string::{String, ToString},
+    sync::{Arc, Mutex, MutexGuard},
+    thread::{self, ThreadId},
+    vec::Vec,
+};
+
+use crate::macros::GlobalCell;
+
+/// What kind of identifier is held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeldKind {
+    /// A [`Type`](crate::typeid::Type) created by [`make_typeid`](crate::make_typeid)
+    TypeId,
+    /// A [`Runtime`](crate::runtime::Runtime)
+    Runtime,
+}
+
+/// An identifier that is currently held, see [`held`]
+#[derive(Clone)]
+pub struct Held {
+    key: Key,
+    kind: HeldKind,
+    type_name: &'static str,
+    id: Option<String>,
+    thread_id: ThreadId,
+    thread_name: Option<String>,
+    backtrace: Arc<Backtrace>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Key {
+    TypeId(&'static str),
+    Runtime(u64),
+}
+
+impl Held {
+    /// The kind of identifier
+    #[inline]
+    pub fn kind(&self) -> HeldKind { self.kind }
+
+    /// The type name of the identifier
+    #[inline]
+    pub fn type_name(&self) -> &'static str { self.type_name }
+
+    /// The `Debug` output of the [`Runtime`](crate::runtime::Runtime)'s id
+    ///
+    /// This is `None` for [`Type`](crate::typeid::Type)s, and for `Runtime`s whose
+    /// [`IdAlloc`](crate::runtime::IdAlloc) can't show their ids, see
+    /// [`IdAlloc::debug_id`](crate::runtime::IdAlloc::debug_id)
+    #[inline]
+    pub fn id(&self) -> Option<&str> { self.id.as_deref() }
+
+    /// The thread that created the identifier
+    #[inline]
+    pub fn thread_id(&self) -> ThreadId { self.thread_id }
+
+    /// The name of the thread that created the identifier, if it has one
+    #[inline]
+    pub fn thread_name(&self) -> Option<&str> { self.thread_name.as_deref() }
+
+    /// Where the identifier was created, if backtraces are enabled
+    #[inline]
+    pub fn backtrace(&self) -> &Backtrace { &self.backtrace }
+}
+
+impl fmt::Debug for Held {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Held")
+            .field("kind", &self.kind)
+            .field("type_name", &self.type_name)
+            .field("id", &self.id)
+            .field("thread_id", &self.thread_id)
+            .field("thread_name", &self.thread_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Held {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` ", self.type_name)?;
+
+        if let Some(id) = &self.id {
+            write!(f, "with the id {} ", id)?;
+        }
+
+        write!(f, "was created on thread ")?;
+
+        match &self.thread_name {
+            Some(name) => write!(f, "`{}` ({:?})", name, self.thread_id)?,
+            None => write!(f, "{:?}", self.thread_id)?,
+        }
+
+        if self.backtrace.status() == BacktraceStatus::Captured {
+            write!(f, " at\n{}", self.backtrace)?;
+        }
+
+        Ok(())
+    }
+}
+
+static HELD: GlobalCell<Mutex<Vec<Held>>> = GlobalCell::new();
+
+fn registry() -> MutexGuard<'static, Vec<Held>> {
+    match HELD.get_or_init(Mutex::default).lock() {
+        Ok(held) => held,
+        Err(poison) => poison.into_inner(),
+    }
+}
+
+/// All of the identifiers that are currently held, in the order that they were created
+pub fn held() -> Vec<Held> { registry().clone() }
+
+/// Assert that no identifiers are held, this is useful at the end of tests
+///
+/// # Panic
+///
+/// If any identifiers are held, with a message that lists them
+#[track_caller]
+pub fn assert_none_held() {
+    let held = held();
+
+    if !held.is_empty() {
+        let mut message = std::format!("{} identifier(s) are still held", held.len());
+        for held in &held {
+            message.push_str("\n * ");
+            message.push_str(&held.to_string());
+        }
+        panic!("{}", message)
+    }
+}
+
+fn insert(key: Key, kind: HeldKind, type_name: &'static str, id: Option<String>) {
+    let thread = thread::current();
+    let held = Held {
+        key,
+        kind,
+        type_name,
+        id,
+        thread_id: thread.id(),
+        thread_name: thread.name().map(String::from),
+        backtrace: Arc::new(Backtrace::capture()),
+    };
+    registry().push(held);
+}
+
+fn remove(key: Key) {
+    let mut held = registry();
+    if let Some(index) = held.iter().rposition(|held| held.key == key) {
+        held.remove(index);
+    }
+}
+
+pub(crate) fn typeid_acquired<T>() {
+    let name = core::any::type_name::<T>();
+    insert(Key::TypeId(name), HeldKind::TypeId, name, None)
+}
+
+pub(crate) fn typeid_released<T>() { remove(Key::TypeId(core::any::type_name::<T>())) }
+
+/// The record of a [`Runtime`](crate::runtime::Runtime), which is removed on drop
+pub(crate) struct Tracked(u64);
+
+impl Tracked {
+    pub fn new<I>(id: Option<&dyn fmt::Debug>) -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let key = NEXT.fetch_add(1, Relaxed);
+        let id = id.map(|id| std::format!("{:?}", id));
+        insert(Key::Runtime(key), HeldKind::Runtime, core::any::type_name::<I>(), id);
+        Self(key)
+    }
+
+    /// Record the new id after a [`Runtime`](crate::runtime::Runtime) was rekeyed
+    pub fn rekey(&self, id: Option<&dyn fmt::Debug>) {
+        let id = id.map(|id| std::format!("{:?}", id));
+        if let Some(held) = registry().iter_mut().rfind(|held| held.key == Key::Runtime(self.0)) {
+            held.id = id;
+        }
+    }
+
+    /// Remove the record before the [`Runtime`](crate::runtime::Runtime) is dropped
+    pub fn untrack(&self) { remove(Key::Runtime(self.0)) }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) { remove(Key::Runtime(self.0)) }
+}
@@ -12,16 +12,31 @@
 //! ### features
 //!
 //! * `std` (default) - if you have the `std` feature on, it will supercede the `alloc` feature.
-//!     This allows you to use:
+//!   This allows you to use:
 //!      * `std` types to implement various traits, for example `Box<I>` will implemnt `Identifier` `I`
 //!      * `thread_local` types (from the `*_tl`)
 //!      * `make_global_reuse` (this requires internal locking using a `Mutex`)
+//!      * [`thread_owner`] and [`with_thread_owner`], a lazily created identifier for each thread
 //!
 //! * `alloc` - this allows you to use without pulling in all of `std`:
 //!      * `alloc` types to implement various traits, for example `Box<I>` will implemnt `Identifier` `I`
 //!
 //! * `nightly` -  this allows you to use:
 //!      * atomics on `no_std` targets that don't support 64-bit atomics
+//!
+//! * `debug-names` - this requires `std`, and allows you to use:
+//!      * [`Runtime::new_named`](runtime::Runtime::new_named), which shows up in the `Debug` output
+//!        of the runtime and its handles
+//!
+//! * `stats` - this allows you to use:
+//!      * [`IdAllocStats`](runtime::IdAllocStats), which keeps track of the `Runtime`s created
+//!        from the allocators made by [`make_global_id_alloc`]
+//!
+//! * `track-leaks` - this requires `std`, and allows you to use:
+//!      * [`leaks`], which lists the typeids and runtime identifiers that are currently held
+//!
+//! * `derive` - this allows you to use:
+//!      * `#[derive(Identifier)]` and `#[derive(Handle)]` for newtypes around identifiers and handles
 
 #[cfg(feature = "std")]
 extern crate std;
@@ -29,21 +44,66 @@ extern crate std;
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 extern crate alloc as std;
 
+// Implements `SharedIdentifier` for an `Identifier` by forwarding to its `owns` and `check`
+//
+// This is safe for any `Identifier`, because a `&mut` reference to the identifier
+// can't exist while it is borrowed to call `owns_shared` or `check_shared`
+macro_rules! forward_shared_identifier {
+    ([$($generics:tt)*] $type:ty $(where $($bounds:tt)*)?) => {
+        unsafe impl<$($generics)*> crate::SharedIdentifier for $type $(where $($bounds)*)? {
+            type Handle = <Self as crate::Identifier>::Handle;
+
+            #[inline]
+            fn owns_shared(&self, handle: &Self::Handle) -> bool { crate::Identifier::owns(self, handle) }
+
+            #[inline]
+            fn check_shared(&self, handle: &Self::Handle) -> Result<(), crate::OwnershipError> {
+                crate::Identifier::check(self, handle)
+            }
+        }
+    };
+}
+
 #[doc(hidden)]
 pub mod macros;
 
+pub mod any;
+pub mod cell;
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub mod grid;
+#[cfg(feature = "track-leaks")]
+#[cfg_attr(doc, doc(cfg(feature = "track-leaks")))]
+pub mod leaks;
+pub mod lock;
 pub mod runtime;
 pub mod scoped;
+pub mod slice;
 pub mod typeid;
 #[cfg(any(feature = "std", doc))]
 #[cfg_attr(doc, doc(cfg(feature = "std")))]
 pub mod typeid_tl;
+pub mod utf8;
+
+mod ownership;
+#[cfg(feature = "std")]
+mod thread_owner;
+#[cfg(feature = "std")]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub use thread_owner::{
+    thread_owner, try_with_thread_owner, with_thread_owner, ThreadOwner, ThreadOwnerHandle, ThreadOwnerId,
+};
 
 #[cfg(all(feature = "test", feature = "std"))]
 #[doc(hidden)]
 pub mod test_setup;
 
 pub use macros::Scalar;
+pub use ownership::OwnershipError;
+
+#[cfg(feature = "derive")]
+#[cfg_attr(doc, doc(cfg(feature = "derive")))]
+pub use pui_derive::{Handle, Identifier};
 
 struct Invariant<T: ?Sized>(fn() -> *mut T);
 #[doc(hidden)]
@@ -117,14 +177,37 @@ pub struct ThreadLocal(*mut ());
 /// # Safety
 ///
 /// * `ident.owns(&handle)` must return true for any `handle` returned
-///     from `ident.handle()` regardless of when the handle was created.
+///   from `ident.handle()`, until `ident` is next accessed through a `&mut` reference.
+///     * A `&mut` reference may replace the identifier, or change which handles it owns,
+///       like [`Runtime::rekey`](crate::runtime::Runtime::rekey) does. So code that caches
+///       a handle must check it again after handing out a `&mut` to the identifier.
 /// * If two handles compare equal, then `Identifier::owns` must act the
-///     same for both of them
+///   same for both of them
 ///     * i.e. it must return false for both handles, or it must return
-///         true for both handles
+///       true for both handles
 /// * Two instances of `Identifier` must *never* return true for the same
-///     handle if they can both exist on the same thread.
+///   handle if they can both exist on the same thread.
+/// * If `Self::Handle` is `Send` or `Sync`, then two instances of `Identifier`
+///   must *never* return true for the same handle at the same time, even if they
+///   exist on different threads.
+///     * Identifiers which are only unique on a single thread must use a handle that is
+///       neither `Send` nor `Sync`, for example by storing a `PhantomData<*mut ()>` in it
+///     * This allows cells that are branded by `Send + Sync` handles, like
+///       [`IdCell`](crate::cell::IdCell), to be shared between threads
 /// * In particular, it is unsound to implement `Identifier` on references
+///
+/// Tuples of up to 4 identifiers are identifiers, whose handle is the tuple of the
+/// elements' handles. A tuple only owns a handle if *every* element owns its part of
+/// the handle, so any two tuples that own the same handle would need two elements that
+/// own the same handle, which the elements already forbid. The tuple's handle is only
+/// `Send` or `Sync` if every element's handle is, so the thread rules above carry over as well.
+/// Owning just *some* of the parts is not enough, so an identifier that combines other identifiers
+/// must check all of them.
+///
+/// Identifiers are not automatically [`SharedIdentifier`]s, if you implement `Identifier` yourself,
+/// you should also implement `SharedIdentifier` by forwarding to `owns` and `check`, so that the
+/// identifier can be used with shared APIs like [`IdCell::borrow`](crate::cell::IdCell::borrow).
+/// `#[derive(Identifier)]` implements both.
 pub unsafe trait Identifier: Eq {
     /// A handle which can be used to mark other types
     type Handle: Handle;
@@ -134,6 +217,140 @@ pub unsafe trait Identifier: Eq {
 
     /// Check the current identifier owns the given handle
     fn owns(&self, handle: &Self::Handle) -> bool;
+
+    /// Check the current identifier owns the given handle,
+    /// and describe the mismatch if it doesn't
+    ///
+    /// see also [`assert_owns`](crate::assert_owns)
+    #[inline]
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError> {
+        if self.owns(handle) {
+            Ok(())
+        } else {
+            Err(OwnershipError::from_types::<Self, Self::Handle>())
+        }
+    }
+}
+
+/// An [`Identifier`] whose handles are never owned by another identifier, not even after it is dropped
+///
+/// The [`Identifier`] rules only forbid two identifiers from owning the same handle *at the same time*,
+/// so a handle may be owned by a new identifier later on, for example when a pooled
+/// [`Runtime`](crate::runtime::Runtime) reuses an id. Collections that trust a handle to always
+/// refer to the same collection, like [`BrandedSlice`](crate::slice::BrandedSlice), require this.
+///
+/// This is implemented for [`Scoped`](crate::scoped::Scoped), and for [`Runtime`](crate::runtime::Runtime)s
+/// that don't use a pool.
+///
+/// # Safety
+///
+/// * After `ident.owns(&handle)` returned true, no other identifier may ever return true
+///   for `handle`, or any handle that compares equal to it, even after `ident` is dropped.
+/// * If `Self::Handle` is neither `Send` nor `Sync`, then this only needs to hold on the
+///   thread that created `ident`.
+pub unsafe trait OneShotIdentifier: Identifier {}
+
+/// A shared proof of ownership, which only grants shared access
+///
+/// Every [`Identifier`] in this crate is a `SharedIdentifier`, and so are `&I`, `Rc<I>`, and `Arc<I>`
+/// for any identifier `I`. APIs that only hand out shared access, like
+/// [`IdCell::borrow`](crate::cell::IdCell::borrow), take a `&S` where `S: SharedIdentifier`,
+/// so they work with a plain `&I` as well as with identifiers that are shared by many readers.
+/// Code that is generic over a `S: SharedIdentifier` taken by value can be passed a `&I`. Exclusive access, like
+/// [`IdCell::borrow_mut`](crate::cell::IdCell::borrow_mut), still needs a unique
+/// `&mut I` where `I: Identifier`, which can't exist while the identifier is shared.
+///
+/// ```
+/// # #[cfg(any(feature = "std", feature = "alloc"))] {
+/// use pui::{cell::IdCell, runtime::Runtime};
+/// use std::sync::Arc;
+///
+/// let mut owner = Runtime::new();
+/// let cell = Arc::new(IdCell::new(owner.handle(), 0));
+///
+/// *cell.borrow_mut(&mut owner) += 1;
+///
+/// let owner = Arc::new(owner);
+/// let threads: Vec<_> = (0..2)
+///     .map(|_| {
+///         let owner = owner.clone();
+///         let cell = cell.clone();
+///         std::thread::spawn(move || assert_eq!(*cell.borrow(&owner), 1))
+///     })
+///     .collect();
+///
+/// for thread in threads {
+///     thread.join().unwrap();
+/// }
+///
+/// // once the identifier is unique again, it can give exclusive access again
+/// let mut owner = Arc::try_unwrap(owner).unwrap();
+/// *cell.borrow_mut(&mut owner) += 1;
+/// # }
+/// ```
+///
+/// # Safety
+///
+/// * If `owns_shared` returns true for a handle, then there must be an [`Identifier`] that owns
+///   the handle, and no `&mut` reference to that identifier may exist while `self` is borrowed
+/// * `owns_shared` must return the same result for equal handles, and `check_shared` must
+///   succeed if and only if `owns_shared` returns true
+pub unsafe trait SharedIdentifier {
+    /// A handle which can be used to mark other types
+    type Handle: Handle;
+
+    /// Check if the shared identifier owns the given handle
+    fn owns_shared(&self, handle: &Self::Handle) -> bool;
+
+    /// Check if the shared identifier owns the given handle,
+    /// and describe the mismatch if it doesn't
+    #[inline]
+    fn check_shared(&self, handle: &Self::Handle) -> Result<(), OwnershipError> {
+        if self.owns_shared(handle) {
+            Ok(())
+        } else {
+            Err(OwnershipError::new::<Self>())
+        }
+    }
+}
+
+// These are safe because `&mut I` can't be created while `I` is borrowed,
+// and `Rc::get_mut` and `Arc::get_mut` fail while the pointer is shared.
+// The identifiers in this crate forward to `Identifier` with `forward_shared_identifier`,
+// there is no blanket impl for every `Identifier`, because it would overlap with the impl for `&I`
+
+unsafe impl<I: ?Sized + Identifier> SharedIdentifier for &I {
+    type Handle = I::Handle;
+
+    #[inline]
+    fn owns_shared(&self, handle: &Self::Handle) -> bool { I::owns(self, handle) }
+
+    #[inline]
+    fn check_shared(&self, handle: &Self::Handle) -> Result<(), OwnershipError> { I::check(self, handle) }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+unsafe impl<I: ?Sized + Identifier> SharedIdentifier for std::rc::Rc<I> {
+    type Handle = I::Handle;
+
+    #[inline]
+    fn owns_shared(&self, handle: &Self::Handle) -> bool { I::owns(self, handle) }
+
+    #[inline]
+    fn check_shared(&self, handle: &Self::Handle) -> Result<(), OwnershipError> { I::check(self, handle) }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+unsafe impl<I: ?Sized + Identifier> SharedIdentifier for std::sync::Arc<I> {
+    type Handle = I::Handle;
+
+    #[inline]
+    fn owns_shared(&self, handle: &Self::Handle) -> bool { I::owns(self, handle) }
+
+    #[inline]
+    fn check_shared(&self, handle: &Self::Handle) -> Result<(), OwnershipError> { I::check(self, handle) }
 }
 
 /// A handle to an [`Identifier`](Identifier).
@@ -161,4 +378,50 @@ unsafe impl<I: Identifier + ?Sized> Identifier for std::boxed::Box<I> {
 
     #[inline]
     fn owns(&self, handle: &Self::Handle) -> bool { I::owns(self, handle) }
+
+    #[inline]
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError> { I::check(self, handle) }
 }
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+forward_shared_identifier!([I: Identifier + ?Sized] std::boxed::Box<I>);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+unsafe impl<I: OneShotIdentifier + ?Sized> OneShotIdentifier for std::boxed::Box<I> {}
+
+macro_rules! tuple_impls {
+    ($($name:ident $index:tt),*) => {
+        unsafe impl<$($name: Identifier),*> Identifier for ($($name,)*) {
+            type Handle = ($($name::Handle,)*);
+
+            #[inline]
+            fn handle(&self) -> Self::Handle { ($(self.$index.handle(),)*) }
+
+            #[inline]
+            fn owns(&self, handle: &Self::Handle) -> bool { true $(&& self.$index.owns(&handle.$index))* }
+
+            #[inline]
+            fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError> {
+                $(self.$index.check(&handle.$index)?;)*
+                Ok(())
+            }
+        }
+
+        forward_shared_identifier!([$($name: Identifier),*] ($($name,)*));
+
+        // a tuple only owns a handle if every element owns its part,
+        // so if every element is one-shot, so is the tuple
+        unsafe impl<$($name: OneShotIdentifier),*> OneShotIdentifier for ($($name,)*) {}
+
+        unsafe impl<$($name: Handle),*> Handle for ($($name,)*) {}
+
+        impl<$($name: Trivial),*> Trivial for ($($name,)*) {
+            const INSTANCE: Self = ($($name::INSTANCE,)*);
+        }
+    };
+}
+
+tuple_impls!(A 0);
+tuple_impls!(A 0, B 1);
+tuple_impls!(A 0, B 1, C 2);
+tuple_impls!(A 0, B 1, C 2, D 3);
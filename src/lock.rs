@@ -0,0 +1,350 @@
+//! Locks that guard an [`Identifier`](crate::Identifier)
+//!
+//! Locking one of these gives you access to the identifier, which in turn gives you access
+//! to every [`IdCell`](crate::cell::IdCell) that it owns. So a single lock acquisition
+//! can guard any number of cells, no matter where they are stored.
+//!
+//! * [`IdMutex`] - uses [`std::sync::Mutex`] (requires `std`)
+//! * [`IdRwLock`] - uses [`std::sync::RwLock`] (requires `std`)
+//! * [`IdSpinLock`] - a simple spin lock, which is available without `std`
+//!
+//! The locks cache the identifier's handle when they are created, so that you can
+//! create new cells without locking. Poisoning is ignored, because there is nothing
+//! that a panic could break in the identifier itself.
+//!
+//! The guards give out a `&mut` to the identifier, so it can be replaced or rekeyed
+//! (see [`Runtime::rekey`](crate::runtime::Runtime::rekey)) while the lock is held.
+//! After that, the cached handle is no longer owned by the guarded identifier, so the
+//! cells created with it can't be accessed through the lock anymore. This is never
+//! unsound, because cells check the identifier on every access.
+//!
+//! ```
+//! use pui::{cell::IdCell, lock::IdSpinLock, runtime::Runtime};
+//!
+//! let lock = IdSpinLock::new(Runtime::new());
+//!
+//! let a = IdCell::new(lock.handle(), 1);
+//! let b = IdCell::new(lock.handle(), 2);
+//!
+//! let mut owner = lock.lock();
+//! *a.borrow_mut(&mut *owner) += 1;
+//! *b.borrow_mut(&mut *owner) += 1;
+//! assert_eq!(*a.borrow(&*owner) + *b.borrow(&*owner), 5);
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    fmt, hint,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::Identifier;
+
+#[cfg(feature = "std")]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub use self::std_locks::*;
+
+/// A spin lock that guards an [`Identifier`](crate::Identifier)
+///
+/// see module docs for details
+pub struct IdSpinLock<I: Identifier> {
+    handle: I::Handle,
+    locked: AtomicBool,
+    ident: UnsafeCell<I>,
+}
+
+/// The guard returned by [`IdSpinLock::lock`], the lock is released when it is dropped
+pub struct IdSpinLockGuard<'a, I: Identifier> {
+    lock: &'a IdSpinLock<I>,
+}
+
+// This is safe because the spin lock only ever gives out access to the identifier
+// to one thread at a time, just like `std::sync::Mutex`
+unsafe impl<I: Identifier + Send> Sync for IdSpinLock<I> where I::Handle: Sync {}
+unsafe impl<I: Identifier + Sync> Sync for IdSpinLockGuard<'_, I> {}
+
+impl<I: Identifier> IdSpinLock<I> {
+    /// Create a new lock that guards the given identifier
+    pub fn new(ident: I) -> Self {
+        Self {
+            handle: ident.handle(),
+            locked: AtomicBool::new(false),
+            ident: UnsafeCell::new(ident),
+        }
+    }
+
+    /// The handle of the guarded identifier when this lock was created
+    ///
+    /// see module docs for details
+    #[inline]
+    pub fn handle(&self) -> I::Handle { self.handle.clone() }
+
+    /// Lock the identifier, spinning until it is available
+    pub fn lock(&self) -> IdSpinLockGuard<'_, I> {
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard
+            }
+
+            while self.locked.load(Ordering::Relaxed) {
+                hint::spin_loop();
+            }
+        }
+    }
+
+    /// Try to lock the identifier, returns `None` if it is already locked
+    pub fn try_lock(&self) -> Option<IdSpinLockGuard<'_, I>> {
+        if self
+            .locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            Some(IdSpinLockGuard { lock: self })
+        } else {
+            None
+        }
+    }
+
+    /// Get a mutable reference to the identifier
+    ///
+    /// This doesn't need to lock, because `&mut self` guarantees unique access
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut I { self.ident.get_mut() }
+
+    /// Get the guarded identifier
+    #[inline]
+    pub fn into_inner(self) -> I { self.ident.into_inner() }
+}
+
+impl<I: Identifier> Deref for IdSpinLockGuard<'_, I> {
+    type Target = I;
+
+    // This is safe because we hold the lock
+    #[inline]
+    fn deref(&self) -> &I { unsafe { &*self.lock.ident.get() } }
+}
+
+impl<I: Identifier> DerefMut for IdSpinLockGuard<'_, I> {
+    // This is safe because we hold the lock
+    #[inline]
+    fn deref_mut(&mut self) -> &mut I { unsafe { &mut *self.lock.ident.get() } }
+}
+
+impl<I: Identifier> Drop for IdSpinLockGuard<'_, I> {
+    fn drop(&mut self) { self.lock.locked.store(false, Ordering::Release); }
+}
+
+impl<I: Identifier> fmt::Debug for IdSpinLock<I>
+where
+    I::Handle: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdSpinLock")
+            .field("handle", &self.handle)
+            .field("locked", &self.locked.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl<I: Identifier + fmt::Debug> fmt::Debug for IdSpinLockGuard<'_, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { I::fmt(self, f) }
+}
+
+#[cfg(feature = "std")]
+mod std_locks {
+    use core::{
+        fmt,
+        ops::{Deref, DerefMut},
+    };
+    use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+    use crate::Identifier;
+
+    fn ignore_poison<T>(result: Result<T, TryLockError<T>>) -> Option<T> {
+        match result {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::Poisoned(poison)) => Some(poison.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// A [`Mutex`](std::sync::Mutex) that guards an [`Identifier`](crate::Identifier)
+    ///
+    /// see [module docs](crate::lock) for details
+    pub struct IdMutex<I: Identifier> {
+        handle: I::Handle,
+        ident: Mutex<I>,
+    }
+
+    /// The guard returned by [`IdMutex::lock`], the lock is released when it is dropped
+    pub struct IdMutexGuard<'a, I: Identifier> {
+        guard: MutexGuard<'a, I>,
+    }
+
+    impl<I: Identifier> IdMutex<I> {
+        /// Create a new lock that guards the given identifier
+        pub fn new(ident: I) -> Self {
+            Self {
+                handle: ident.handle(),
+                ident: Mutex::new(ident),
+            }
+        }
+
+        /// The handle of the guarded identifier when this lock was created
+        ///
+        /// see [module docs](crate::lock) for details
+        #[inline]
+        pub fn handle(&self) -> I::Handle { self.handle.clone() }
+
+        /// Lock the identifier, blocking the current thread until it is available
+        pub fn lock(&self) -> IdMutexGuard<'_, I> {
+            IdMutexGuard {
+                guard: self.ident.lock().unwrap_or_else(PoisonError::into_inner),
+            }
+        }
+
+        /// Try to lock the identifier, returns `None` if it is already locked
+        pub fn try_lock(&self) -> Option<IdMutexGuard<'_, I>> {
+            ignore_poison(self.ident.try_lock()).map(|guard| IdMutexGuard { guard })
+        }
+
+        /// Get a mutable reference to the identifier
+        ///
+        /// This doesn't need to lock, because `&mut self` guarantees unique access
+        pub fn get_mut(&mut self) -> &mut I { self.ident.get_mut().unwrap_or_else(PoisonError::into_inner) }
+
+        /// Get the guarded identifier
+        pub fn into_inner(self) -> I { self.ident.into_inner().unwrap_or_else(PoisonError::into_inner) }
+    }
+
+    impl<I: Identifier> Deref for IdMutexGuard<'_, I> {
+        type Target = I;
+
+        #[inline]
+        fn deref(&self) -> &I { &self.guard }
+    }
+
+    impl<I: Identifier> DerefMut for IdMutexGuard<'_, I> {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut I { &mut self.guard }
+    }
+
+    /// A [`RwLock`](std::sync::RwLock) that guards an [`Identifier`](crate::Identifier)
+    ///
+    /// Many threads can read from cells at the same time, but
+    /// only one thread at a time can write to them
+    ///
+    /// see [module docs](crate::lock) for details
+    pub struct IdRwLock<I: Identifier> {
+        handle: I::Handle,
+        ident: RwLock<I>,
+    }
+
+    /// The guard returned by [`IdRwLock::read`], the lock is released when it is dropped
+    pub struct IdRwLockReadGuard<'a, I: Identifier> {
+        guard: RwLockReadGuard<'a, I>,
+    }
+
+    /// The guard returned by [`IdRwLock::write`], the lock is released when it is dropped
+    pub struct IdRwLockWriteGuard<'a, I: Identifier> {
+        guard: RwLockWriteGuard<'a, I>,
+    }
+
+    impl<I: Identifier> IdRwLock<I> {
+        /// Create a new lock that guards the given identifier
+        pub fn new(ident: I) -> Self {
+            Self {
+                handle: ident.handle(),
+                ident: RwLock::new(ident),
+            }
+        }
+
+        /// The handle of the guarded identifier when this lock was created
+        ///
+        /// see [module docs](crate::lock) for details
+        #[inline]
+        pub fn handle(&self) -> I::Handle { self.handle.clone() }
+
+        /// Lock the identifier for shared access, blocking the current thread until it is available
+        pub fn read(&self) -> IdRwLockReadGuard<'_, I> {
+            IdRwLockReadGuard {
+                guard: self.ident.read().unwrap_or_else(PoisonError::into_inner),
+            }
+        }
+
+        /// Try to lock the identifier for shared access, returns `None` if it is locked for unique access
+        pub fn try_read(&self) -> Option<IdRwLockReadGuard<'_, I>> {
+            ignore_poison(self.ident.try_read()).map(|guard| IdRwLockReadGuard { guard })
+        }
+
+        /// Lock the identifier for unique access, blocking the current thread until it is available
+        pub fn write(&self) -> IdRwLockWriteGuard<'_, I> {
+            IdRwLockWriteGuard {
+                guard: self.ident.write().unwrap_or_else(PoisonError::into_inner),
+            }
+        }
+
+        /// Try to lock the identifier for unique access, returns `None` if it is already locked
+        pub fn try_write(&self) -> Option<IdRwLockWriteGuard<'_, I>> {
+            ignore_poison(self.ident.try_write()).map(|guard| IdRwLockWriteGuard { guard })
+        }
+
+        /// Get a mutable reference to the identifier
+        ///
+        /// This doesn't need to lock, because `&mut self` guarantees unique access
+        pub fn get_mut(&mut self) -> &mut I { self.ident.get_mut().unwrap_or_else(PoisonError::into_inner) }
+
+        /// Get the guarded identifier
+        pub fn into_inner(self) -> I { self.ident.into_inner().unwrap_or_else(PoisonError::into_inner) }
+    }
+
+    impl<I: Identifier> Deref for IdRwLockReadGuard<'_, I> {
+        type Target = I;
+
+        #[inline]
+        fn deref(&self) -> &I { &self.guard }
+    }
+
+    impl<I: Identifier> Deref for IdRwLockWriteGuard<'_, I> {
+        type Target = I;
+
+        #[inline]
+        fn deref(&self) -> &I { &self.guard }
+    }
+
+    impl<I: Identifier> DerefMut for IdRwLockWriteGuard<'_, I> {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut I { &mut self.guard }
+    }
+
+    impl<I: Identifier> fmt::Debug for IdMutex<I>
+    where
+        I::Handle: fmt::Debug,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("IdMutex").field("handle", &self.handle).finish()
+        }
+    }
+
+    impl<I: Identifier> fmt::Debug for IdRwLock<I>
+    where
+        I::Handle: fmt::Debug,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("IdRwLock").field("handle", &self.handle).finish()
+        }
+    }
+
+    impl<I: Identifier + fmt::Debug> fmt::Debug for IdMutexGuard<'_, I> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { I::fmt(self, f) }
+    }
+
+    impl<I: Identifier + fmt::Debug> fmt::Debug for IdRwLockReadGuard<'_, I> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { I::fmt(self, f) }
+    }
+
+    impl<I: Identifier + fmt::Debug> fmt::Debug for IdRwLockWriteGuard<'_, I> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { I::fmt(self, f) }
+    }
+}
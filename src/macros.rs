@@ -1,15 +1,20 @@
 pub use core::{
     cell::{Cell, UnsafeCell},
     compile_error, concat,
+    fmt::Debug,
+    format_args,
     marker::PhantomData,
     mem::MaybeUninit,
     ops::Drop,
-    option::Option,
+    option::Option::{self, None, Some},
+    result::Result::Err,
     stringify,
 };
 
+pub use crate::ownership::{ownership_failed, Check, DebugDetails, Details, IdentifierCheck, NoDetails, SharedCheck};
+
 #[cfg(feature = "std")]
-pub use std::sync::{Condvar, Mutex, MutexGuard, Once};
+pub use std::sync::{Mutex, MutexGuard};
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 pub use std::{collections::VecDeque, vec::Vec};
@@ -36,10 +41,80 @@ macro_rules! thread_local {
     };
 }
 
-pub struct OnceFlag(AtomicBool);
+// The synchronization primitives used by the flags below, these are
+// swapped out for `loom`'s versions when model checking (see `tests/loom.rs`)
+mod sync {
+    #[cfg(not(loom))]
+    pub use core::sync::atomic::{AtomicBool, AtomicU8};
+    #[cfg(loom)]
+    pub use loom::sync::atomic::{AtomicBool, AtomicU8};
+
+    #[cfg(all(feature = "std", not(loom)))]
+    pub use crossbeam_utils::Backoff;
+    #[cfg(all(feature = "std", not(loom)))]
+    pub use std::sync::{Condvar, Mutex, Once};
+
+    #[cfg(loom)]
+    pub use loom::sync::{Condvar, Mutex};
+
+    #[cfg(not(any(feature = "std", loom)))]
+    pub use core::hint::spin_loop;
+
+    // `loom` can't model unbounded spinning, so go to sleep after the first failed attempt
+    #[cfg(loom)]
+    pub struct Backoff;
+
+    #[cfg(loom)]
+    impl Backoff {
+        pub fn new() -> Self { Self }
+
+        pub fn spin(&self) { loom::thread::yield_now(); }
+
+        pub fn is_completed(&self) -> bool { true }
+
+        pub fn reset(&self) {}
+    }
+
+    // `loom` doesn't have a `Once`, and it can't model spinning until another thread
+    // finishes initializing, so block on a `Mutex` instead
+    #[cfg(loom)]
+    pub struct Once(Mutex<bool>);
+
+    #[cfg(loom)]
+    impl Once {
+        pub fn new() -> Self { Self(Mutex::new(false)) }
+
+        pub fn call_once<F: FnOnce()>(&self, f: F) {
+            let mut done = self.0.lock().unwrap();
+            if !*done {
+                f();
+                *done = true;
+            }
+        }
+
+        pub fn is_completed(&self) -> bool { *self.0.lock().unwrap() }
+    }
+}
+
+// `loom`'s atomics can't be created in a `const fn`
+macro_rules! const_fn {
+    ($(#[$meta:meta])* $v:vis fn $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$meta])*
+        $v const fn $($rest)*
+
+        #[cfg(loom)]
+        $(#[$meta])*
+        $v fn $($rest)*
+    };
+}
+
+pub struct OnceFlag(sync::AtomicBool);
 
 impl OnceFlag {
-    pub const fn new() -> Self { Self(AtomicBool::new(true)) }
+    const_fn! {
+        pub fn new() -> Self { Self(sync::AtomicBool::new(true)) }
+    }
 
     pub fn take(&self) -> bool {
         self.0
@@ -49,41 +124,27 @@ impl OnceFlag {
 }
 
 cfg_if::cfg_if! {
-    if #[cfg(feature = "std")] {
-        use crossbeam_utils::Backoff;
+    if #[cfg(any(feature = "std", loom))] {
+        use sync::Backoff;
 
         pub struct ResettableOnceFlag {
-            locked: AtomicBool,
-            once: Once,
-            mutex: UnsafeCell<MaybeUninit<Mutex<()>>>,
-            cv: UnsafeCell<MaybeUninit<Condvar>>,
+            locked: sync::AtomicBool,
+            wait: GlobalCell<(sync::Mutex<()>, sync::Condvar)>,
         }
 
-        unsafe impl Send for ResettableOnceFlag {}
-        unsafe impl Sync for ResettableOnceFlag {}
-
         impl ResettableOnceFlag {
-            pub const fn new() -> Self {
-                Self {
-                    locked: AtomicBool::new(false),
-                    once: Once::new(),
-                    mutex: UnsafeCell::new(MaybeUninit::uninit()),
-                    cv: UnsafeCell::new(MaybeUninit::uninit()),
+            const_fn! {
+                pub fn new() -> Self {
+                    Self {
+                        locked: sync::AtomicBool::new(false),
+                        wait: GlobalCell::new(),
+                    }
                 }
             }
 
-            fn init(&self) -> (&Mutex<()>, &Condvar) {
-                unsafe {
-                    let mutex = self.mutex.get().cast::<Mutex<()>>();
-                    let condvar = self.cv.get().cast::<Condvar>();
-
-                    self.once.call_once(|| {
-                        mutex.write(Mutex::new(()));
-                        condvar.write(Condvar::new());
-                    });
-
-                    (&*mutex, &*condvar)
-                }
+            fn init(&self) -> (&sync::Mutex<()>, &sync::Condvar) {
+                let (mutex, cv) = self.wait.get_or_init(|| (sync::Mutex::new(()), sync::Condvar::new()));
+                (mutex, cv)
             }
 
             pub fn acquire(&self) -> bool {
@@ -112,17 +173,24 @@ cfg_if::cfg_if! {
             }
 
             pub fn release(&self) {
-                self.locked.store(false, Release);
+                // this must be a read-modify-write, like the ones in `acquire`, so that
+                // a waiter that was woken up can't still read the old `true`
+                self.locked.swap(false, Release);
 
-                self.init().1.notify_one();
+                let (mutex, cv) = self.init();
+
+                // lock the mutex before notifying, otherwise a waiter that has just seen
+                // `locked == true`, but hasn't started waiting yet, would miss this wakeup
+                drop(mutex.lock());
+                cv.notify_one();
             }
         }
     } else {
-        pub struct ResettableOnceFlag(AtomicBool);
+        pub struct ResettableOnceFlag(sync::AtomicBool);
 
         impl ResettableOnceFlag {
             pub const fn new() -> Self {
-                Self(AtomicBool::new(true))
+                Self(sync::AtomicBool::new(true))
             }
 
             pub fn acquire(&self) -> bool {
@@ -140,10 +208,12 @@ cfg_if::cfg_if! {
     }
 }
 
-pub struct InitFlag(AtomicU8);
+pub struct InitFlag(sync::AtomicU8);
 
 impl InitFlag {
-    pub const fn new() -> Self { Self(AtomicU8::new(0)) }
+    const_fn! {
+        pub fn new() -> Self { Self(sync::AtomicU8::new(0)) }
+    }
 
     pub fn start_init(&self) -> bool { self.0.compare_exchange(0b00, 0b10, Acquire, Acquire).is_ok() }
 
@@ -154,12 +224,155 @@ impl InitFlag {
     pub fn finish_take(&self) { self.0.store(0b00, Release); }
 }
 
+// A lazily initialized value that can be put in a `static`, this is used instead of `static mut`
+pub struct GlobalCell<T> {
+    #[cfg(any(feature = "std", loom))]
+    once: sync::Once,
+    #[cfg(not(any(feature = "std", loom)))]
+    state: sync::AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+#[cfg(not(any(feature = "std", loom)))]
+const GLOBAL_CELL_UNINIT: u8 = 0;
+#[cfg(not(any(feature = "std", loom)))]
+const GLOBAL_CELL_RUNNING: u8 = 1;
+#[cfg(not(any(feature = "std", loom)))]
+const GLOBAL_CELL_INIT: u8 = 2;
+
+unsafe impl<T: Send> Send for GlobalCell<T> {}
+unsafe impl<T: Send + Sync> Sync for GlobalCell<T> {}
+
+impl<T> GlobalCell<T> {
+    const_fn! {
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self {
+                #[cfg(any(feature = "std", loom))]
+                once: sync::Once::new(),
+                #[cfg(not(any(feature = "std", loom)))]
+                state: sync::AtomicU8::new(GLOBAL_CELL_UNINIT),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            }
+        }
+    }
+
+    fn is_init(&self) -> bool {
+        #[cfg(any(feature = "std", loom))]
+        return self.once.is_completed();
+        #[cfg(not(any(feature = "std", loom)))]
+        return self.state.load(Acquire) == GLOBAL_CELL_INIT;
+    }
+
+    #[cfg(any(feature = "std", loom))]
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        let value = self.value.get().cast::<T>();
+        // `call_once` guarantees that `value` is written exactly once,
+        // and that the write is visible once `call_once` returns
+        self.once.call_once(|| unsafe { value.write(f()) });
+        unsafe { &*value }
+    }
+
+    #[cfg(not(any(feature = "std", loom)))]
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        let value = self.value.get().cast::<T>();
+
+        loop {
+            match self
+                .state
+                .compare_exchange(GLOBAL_CELL_UNINIT, GLOBAL_CELL_RUNNING, Acquire, Acquire)
+            {
+                Ok(_) => {
+                    // if `f` panics, let the next caller try again
+                    struct Reset<'a>(&'a sync::AtomicU8);
+
+                    impl Drop for Reset<'_> {
+                        fn drop(&mut self) { self.0.store(GLOBAL_CELL_UNINIT, Release); }
+                    }
+
+                    let reset = Reset(&self.state);
+                    // only the thread that moved the state to `RUNNING` can get here
+                    unsafe { value.write(f()) }
+                    core::mem::forget(reset);
+                    self.state.store(GLOBAL_CELL_INIT, Release);
+                    break
+                }
+                Err(GLOBAL_CELL_INIT) => break,
+                Err(_) => sync::spin_loop(),
+            }
+        }
+
+        unsafe { &*value }
+    }
+}
+
+impl<T> Drop for GlobalCell<T> {
+    fn drop(&mut self) {
+        if self.is_init() {
+            unsafe { self.value.get().cast::<T>().drop_in_place() }
+        }
+    }
+}
+
+// A slot that can hold a single value, and can be shared between threads
+pub struct AtomicSlot<T> {
+    flag: InitFlag,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T: Send> Send for AtomicSlot<T> {}
+unsafe impl<T: Send> Sync for AtomicSlot<T> {}
+
+impl<T> AtomicSlot<T> {
+    const_fn! {
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self {
+                flag: InitFlag::new(),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            }
+        }
+    }
+
+    pub fn try_put(&self, value: T) -> Result<(), T> {
+        if self.flag.start_init() {
+            // `start_init` gives us unique access to the empty slot until `finish_init`
+            unsafe { self.value.get().cast::<T>().write(value) }
+            self.flag.finish_init();
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+
+    pub fn take(&self) -> Option<T> {
+        if self.flag.start_take() {
+            // `start_take` gives us unique access to the full slot until `finish_take`
+            let value = unsafe { self.value.get().cast::<T>().read() };
+            self.flag.finish_take();
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Drop for AtomicSlot<T> {
+    fn drop(&mut self) { drop(self.take()); }
+}
+
+// A stand-in for `std::thread::LocalKey` without `std`, so that the expansion of `thread_local!`
+// type checks. That expansion also emits a `compile_error!`, so these methods can never run.
 pub struct LocalKey<T>(PhantomData<T>);
 
 impl<T> LocalKey<T> {
     pub const fn new() -> Self { LocalKey(PhantomData) }
 
-    pub fn with<F: FnOnce(&T) -> R, R>(&self, _: F) -> R { todo!() }
+    pub fn with<F: FnOnce(&T) -> R, R>(&self, _: F) -> R { unreachable!("thread locals require `std`") }
+
+    // the error type doesn't matter, because this can never return
+    #[allow(clippy::result_unit_err)]
+    pub fn try_with<F: FnOnce(&T) -> R, R>(&self, _: F) -> Result<R, ()> { unreachable!("thread locals require `std`") }
 }
 
 pub struct LocalOnceFlag(Cell<bool>);
@@ -184,13 +397,41 @@ impl<T> MacroConstructed<T> {
     pub const unsafe fn new() -> Self { Self(PhantomData) }
 }
 
+/// A handle that wraps another handle, this is implemented by `derive(Handle)`
+///
+/// # Safety
+///
+/// `inner(&wrap(handle))` must return `handle`, and two wrapped handles are equal
+/// if and only if their inner handles are equal
+pub unsafe trait HandleNewtype: crate::Handle {
+    type Inner: crate::Handle;
+
+    fn wrap(inner: Self::Inner) -> Self;
+
+    fn inner(&self) -> &Self::Inner;
+}
+
+/// Record that a typeid was acquired, if the `track-leaks` feature is on
+#[inline]
+pub fn typeid_acquired<T>() {
+    #[cfg(feature = "track-leaks")]
+    crate::leaks::typeid_acquired::<T>()
+}
+
+/// Record that a typeid was released, if the `track-leaks` feature is on
+#[inline]
+pub fn typeid_released<T>() {
+    #[cfg(feature = "track-leaks")]
+    crate::leaks::typeid_released::<T>()
+}
+
 pub(crate) use private::Private;
 mod private {
     pub trait Private {}
 }
 
 /// a type that can be used as the backing type in `make_global_id_alloc` an `make_global_id_alloc_tl`
-pub unsafe trait Scalar: Private + Copy + Eq {
+pub unsafe trait Scalar: Private + crate::any::Erasable {
     #[doc(hidden)]
     type Local;
     #[doc(hidden)]
@@ -200,6 +441,9 @@ pub unsafe trait Scalar: Private + Copy + Eq {
     const INIT_LOCAL: Self::Local;
     #[doc(hidden)]
     const INIT_ATOMIC: Self::Atomic;
+    /// The number of ids that can be created from this scalar
+    #[doc(hidden)]
+    const CAPACITY: u128;
 
     #[doc(hidden)]
     fn inc_local(_: Self::Local) -> Option<(Self::Local, Self)>;
@@ -208,6 +452,7 @@ pub unsafe trait Scalar: Private + Copy + Eq {
 }
 
 impl Private for () {}
+unsafe impl crate::any::Erasable for () {}
 impl crate::Trivial for () {
     const INSTANCE: Self = ();
 }
@@ -221,6 +466,8 @@ unsafe impl Scalar for () {
     const INIT_LOCAL: Self::Local = true;
     #[doc(hidden)]
     const INIT_ATOMIC: Self::Atomic = AtomicBool::new(true);
+    #[doc(hidden)]
+    const CAPACITY: u128 = 1;
 
     #[doc(hidden)]
     fn inc_local(this: Self::Local) -> Option<(Self::Local, Self)> {
@@ -254,6 +501,8 @@ macro_rules! num {
         #[cfg_attr(feature = "nightly", cfg(target_has_atomic = $cfg))]
         impl Private for $num {}
         #[cfg_attr(feature = "nightly", cfg(target_has_atomic = $cfg))]
+        unsafe impl crate::any::Erasable for $num {}
+        #[cfg_attr(feature = "nightly", cfg(target_has_atomic = $cfg))]
         unsafe impl Scalar for $num {
             #[doc(hidden)]
             type Local = $local;
@@ -263,6 +512,16 @@ macro_rules! num {
             const INIT_LOCAL: Self::Local = $min;
             #[doc(hidden)]
             const INIT_ATOMIC: Self::Atomic = <$atomic>::new($min);
+            #[doc(hidden)]
+            const CAPACITY: u128 = {
+                // the last value is never handed out, see `inc_local`
+                let max: Option<$local> = $max;
+                let end = match max {
+                    Some(max) => max as i128 - 1,
+                    None => <$local>::MAX as i128,
+                };
+                (end - $min as i128) as u128
+            };
 
             #[doc(hidden)]
             fn inc_local(value: Self::Local) -> Option<(Self::Local, Self)> {
@@ -0,0 +1,224 @@
+use core::{any::type_name, fmt};
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+use std::string::String;
+
+/// The error returned by [`Identifier::check`](crate::Identifier::check) and
+/// [`SharedIdentifier::check_shared`](crate::SharedIdentifier::check_shared)
+/// if the identifier doesn't own the handle
+///
+/// This always records the type names of the identifier and the handle.
+/// With `alloc`, it can also record the identifier and the handle themselves,
+/// see [`OwnershipError::with_details`]
+#[derive(Clone, PartialEq, Eq)]
+pub struct OwnershipError {
+    identifier: &'static str,
+    handle: &'static str,
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    details: Option<String>,
+}
+
+impl OwnershipError {
+    /// Create a new error for the given identifier type
+    pub fn new<I: ?Sized + crate::SharedIdentifier>() -> Self { Self::from_types::<I, I::Handle>() }
+
+    // `Identifier::check` uses this directly, because identifiers aren't always `SharedIdentifier`s
+    pub(crate) fn from_types<I: ?Sized, H: ?Sized>() -> Self {
+        Self {
+            identifier: type_name::<I>(),
+            handle: type_name::<H>(),
+            #[cfg(any(feature = "std", feature = "alloc"))]
+            details: None,
+        }
+    }
+
+    /// Record the identifier and the handle that didn't match
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+    pub fn with_details<I: ?Sized + fmt::Debug, H: ?Sized + fmt::Debug>(mut self, ident: &I, handle: &H) -> Self {
+        self.details = Some(std::format!("identifier: {:?}, handle: {:?}", ident, handle));
+        self
+    }
+
+    /// The type name of the identifier
+    #[inline]
+    pub fn identifier_type(&self) -> &'static str { self.identifier }
+
+    /// The type name of the handle
+    #[inline]
+    pub fn handle_type(&self) -> &'static str { self.handle }
+
+    fn has_details(&self) -> bool {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        return self.details.is_some();
+        #[cfg(not(any(feature = "std", feature = "alloc")))]
+        return false;
+    }
+
+    /// The identifier and the handle that didn't match, if they were recorded
+    #[inline]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+    pub fn details(&self) -> Option<&str> { self.details.as_deref() }
+}
+
+impl fmt::Debug for OwnershipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("OwnershipError");
+        f.field("identifier", &self.identifier).field("handle", &self.handle);
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        f.field("details", &self.details);
+        f.finish()
+    }
+}
+
+impl fmt::Display for OwnershipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` doesn't own the `{}`", self.identifier, self.handle)?;
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        if let Some(details) = &self.details {
+            write!(f, " ({})", details)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+impl std::error::Error for OwnershipError {}
+
+#[doc(hidden)]
+pub struct Details<'a, I, H>(pub &'a I, pub &'a H);
+
+#[doc(hidden)]
+pub struct Check<'a, I: ?Sized>(pub &'a I);
+
+// `assert_owns` calls `(&Check(ident)).check(handle)`, which picks `IdentifierCheck` if the
+// identifier is an `Identifier`, because it doesn't need an extra autoref. This way generic
+// code that only knows `I: Identifier` can use `assert_owns` too
+#[doc(hidden)]
+pub trait IdentifierCheck {
+    type Handle;
+
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError>;
+}
+
+#[doc(hidden)]
+pub trait SharedCheck {
+    type Handle;
+
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError>;
+}
+
+impl<I: ?Sized + crate::Identifier> IdentifierCheck for Check<'_, I> {
+    type Handle = I::Handle;
+
+    #[inline]
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError> { self.0.check(handle) }
+}
+
+impl<S: ?Sized + crate::SharedIdentifier> SharedCheck for &Check<'_, S> {
+    type Handle = S::Handle;
+
+    #[inline]
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError> { self.0.check_shared(handle) }
+}
+
+type DebugPair<'a> = Option<(&'a dyn fmt::Debug, &'a dyn fmt::Debug)>;
+
+// `assert_owns` calls `(&Details(..)).details()`, which picks `DebugDetails` if both the
+// identifier and the handle implement `Debug`, because it doesn't need an extra autoref
+#[doc(hidden)]
+pub trait DebugDetails {
+    fn details(&self) -> DebugPair<'_>;
+}
+
+#[doc(hidden)]
+pub trait NoDetails {
+    fn details(&self) -> DebugPair<'_>;
+}
+
+impl<I: fmt::Debug, H: fmt::Debug> DebugDetails for Details<'_, I, H> {
+    fn details(&self) -> DebugPair<'_> { Some((self.0, self.1)) }
+}
+
+impl<I, H> NoDetails for &Details<'_, I, H> {
+    fn details(&self) -> DebugPair<'_> { None }
+}
+
+#[doc(hidden)]
+#[cold]
+#[inline(never)]
+#[track_caller]
+pub fn ownership_failed(err: OwnershipError, details: DebugPair<'_>, message: Option<fmt::Arguments<'_>>) -> ! {
+    struct WithDetails<'a>(OwnershipError, DebugPair<'a>);
+
+    impl fmt::Display for WithDetails<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(&self.0, f)?;
+            match self.1 {
+                Some((ident, handle)) if !self.0.has_details() => {
+                    write!(f, " (identifier: {:?}, handle: {:?})", ident, handle)
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    let err = WithDetails(err, details);
+
+    match message {
+        Some(message) => panic!("{}: {}", message, err),
+        None => panic!("{}", err),
+    }
+}
+
+/// Assert that an [`Identifier`](crate::Identifier) or a
+/// [`SharedIdentifier`](crate::SharedIdentifier) owns a handle
+///
+/// If it doesn't, this panics with the [`OwnershipError`](crate::OwnershipError) returned by
+/// [`Identifier::check`](crate::Identifier::check), or by
+/// [`SharedIdentifier::check_shared`](crate::SharedIdentifier::check_shared) if it isn't an `Identifier`. If both the identifier and the handle
+/// implement `Debug`, the panic message will also show them. You can add your own message,
+/// just like with `assert!`
+///
+/// ```should_panic
+/// use pui::runtime::Runtime;
+///
+/// let owner = Runtime::new();
+/// let other = Runtime::new();
+///
+/// // panics with a message like
+/// // "`pui::runtime::Runtime` doesn't own the `pui::runtime::RuntimeHandle`
+/// //  (identifier: Runtime(GlobalId(..)), handle: RuntimeHandle { inner: GlobalId(..) })"
+/// pui::assert_owns!(owner, other.handle());
+/// ```
+#[macro_export]
+macro_rules! assert_owns {
+    ($ident:expr, $handle:expr $(,)?) => {
+        match (&$ident, &$handle) {
+            (ident, handle) => {
+                #[allow(unused_imports)]
+                use $crate::macros::{DebugDetails as _, IdentifierCheck as _, NoDetails as _, SharedCheck as _};
+                if let $crate::macros::Err(err) = (&$crate::macros::Check(ident)).check(handle) {
+                    $crate::macros::ownership_failed(err, (&$crate::macros::Details(ident, handle)).details(), $crate::macros::None)
+                }
+            }
+        }
+    };
+    ($ident:expr, $handle:expr, $($arg:tt)+) => {
+        match (&$ident, &$handle) {
+            (ident, handle) => {
+                #[allow(unused_imports)]
+                use $crate::macros::{DebugDetails as _, IdentifierCheck as _, NoDetails as _, SharedCheck as _};
+                if let $crate::macros::Err(err) = (&$crate::macros::Check(ident)).check(handle) {
+                    $crate::macros::ownership_failed(
+                        err,
+                        (&$crate::macros::Details(ident, handle)).details(),
+                        $crate::macros::Some($crate::macros::format_args!($($arg)+)),
+                    )
+                }
+            }
+        }
+    };
+}
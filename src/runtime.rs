@@ -15,9 +15,30 @@
 //!
 // You can use any primitive integer type and their non-zero variants
 
+mod child;
+#[cfg(feature = "std")]
+mod liveness;
 mod macros;
+#[cfg(feature = "debug-names")]
+mod names;
+mod observer;
 mod pool;
+#[cfg(feature = "std")]
+mod randomized;
+#[cfg(feature = "stats")]
+mod stats;
+pub use child::{ChildHandle, ChildRuntime, Lineage};
+#[cfg(feature = "std")]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub use liveness::{LivenessSet, TrackLiveness, WeakRuntime};
+pub use observer::IdAllocObserver;
 pub use pool::*;
+#[cfg(feature = "std")]
+#[cfg_attr(doc, doc(cfg(feature = "std")))]
+pub use randomized::{Randomized, RandomizedId};
+#[cfg(feature = "stats")]
+#[cfg_attr(doc, doc(cfg(feature = "stats")))]
+pub use stats::IdAllocStats;
 
 /// an opaque [`Runtime`] id
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -55,6 +76,52 @@ pub unsafe trait IdAlloc {
 
     /// Try to get the next id, returns `None` if there are no next ids
     fn try_alloc(&mut self) -> Option<Self::Id>;
+
+    /// Called after a [`Runtime`] reused an id from a pool
+    ///
+    /// see [`IdAllocObserver::on_recycle`]
+    #[inline]
+    fn on_recycle(_id: &Self::Id)
+    where
+        Self: Sized,
+    {
+    }
+
+    /// Called when a [`Runtime`] is dropped, before its id is returned to the pool
+    ///
+    /// see [`IdAllocObserver::on_release`]
+    #[inline]
+    fn on_release(_id: &Self::Id)
+    where
+        Self: Sized,
+    {
+    }
+
+    /// Statistics about the [`Runtime`]s created from this allocator, if it keeps track of them
+    ///
+    /// The allocators created by [`make_global_id_alloc`](crate::make_global_id_alloc) keep track of them
+    #[cfg(feature = "stats")]
+    #[cfg_attr(doc, doc(cfg(feature = "stats")))]
+    #[inline]
+    fn stats() -> Option<&'static IdAllocStats>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Show an id in the details of an [`OwnershipError`], if the id implements `Debug`
+    ///
+    /// [`Runtime::check`](crate::Identifier::check) uses this to record the ids that didn't match,
+    /// because it can't require `Self::Id: Debug`. The allocators created by
+    /// [`make_global_id_alloc`](crate::make_global_id_alloc) show their ids
+    #[inline]
+    fn debug_id(_id: &Self::Id) -> Option<&dyn fmt::Debug>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 macro_rules! make_global {
@@ -116,6 +183,10 @@ make_global! {
 pub struct Runtime<I: IdAlloc = Global, P: PoolMut<I::Id> = ()> {
     id: I::Id,
     pool: P,
+    #[cfg(feature = "debug-names")]
+    name: Option<names::Name<I::Id>>,
+    #[cfg(feature = "track-leaks")]
+    _tracked: crate::leaks::Tracked,
 }
 
 /// A handle to a [`Runtime`](Runtime) identifier
@@ -125,12 +196,50 @@ pub struct RuntimeHandle<I: IdAlloc = Global>(pub I::Id);
 impl Runtime {
     /// Create a new [`Runtime`] using [`Global`](Global) without reusing ids
     pub fn new() -> Self { Self::with_id_alloc_and_pool(&mut Global, ()) }
+
+    /// Create a new [`Runtime`] using [`Global`](Global) without reusing ids,
+    /// and give it a name that shows up in the `Debug` output of it and its handles
+    ///
+    /// ```
+    /// use pui::runtime::Runtime;
+    ///
+    /// let runtime = Runtime::new_named("parser-arena");
+    /// assert!(format!("{:?}", runtime.handle()).contains("parser-arena"));
+    /// ```
+    #[cfg(feature = "debug-names")]
+    #[cfg_attr(doc, doc(cfg(feature = "debug-names")))]
+    pub fn new_named(name: &'static str) -> Self { Self::new().named(name) }
 }
 
 impl<P: PoolMut<GlobalId>> Runtime<Global, P> {
     /// Create a new [`Runtime`] using [`Global`](Global), reusing ids from the
     /// given pool
     pub fn with_pool(pool: P) -> Self { Self::with_id_alloc_and_pool(&mut Global, pool) }
+
+    /// Swap in a new id from [`Global`](Global), so that this [`Runtime`] no longer owns
+    /// any of the handles that it created before
+    ///
+    /// This allows branded collections to invalidate all of their indices in `O(1)`
+    ///
+    /// ```
+    /// use pui::{runtime::Runtime, Identifier};
+    ///
+    /// let mut runtime = Runtime::new();
+    /// let old = runtime.handle();
+    ///
+    /// runtime.rekey();
+    /// assert!(!runtime.owns(&old));
+    /// assert!(runtime.owns(&runtime.handle()));
+    /// ```
+    ///
+    /// see [`Runtime::rekey_with_id_alloc`] for details
+    pub fn rekey(&mut self) { self.rekey_with_id_alloc(&mut Global) }
+
+    /// Try to swap in a new id from [`Global`](Global), returns false and leaves this
+    /// [`Runtime`] unchanged if there are no more ids
+    ///
+    /// see [`Runtime::rekey_with_id_alloc`] for details
+    pub fn try_rekey(&mut self) -> bool { self.try_rekey_with_id_alloc(&mut Global) }
 }
 
 impl<I: IdAlloc> Runtime<I> {
@@ -147,11 +256,28 @@ impl<I: IdAlloc, P: PoolMut<I::Id>> Runtime<I, P> {
     /// the [`IdAlloc`]
     pub fn with_id_alloc_and_pool(id_alloc: &mut I, mut pool: P) -> Self {
         let id = match pool.take_mut() {
-            Some(id_alloc) => id_alloc.0,
-            None => id_alloc.alloc(),
+            Some(id_alloc) => {
+                I::on_recycle(&id_alloc.0);
+                #[cfg(feature = "stats")]
+                stats::recycled::<I>();
+                id_alloc.0
+            }
+            None => {
+                let id = id_alloc.alloc();
+                #[cfg(feature = "stats")]
+                stats::fresh::<I>();
+                id
+            }
         };
 
-        Runtime { id, pool }
+        Runtime {
+            id,
+            pool,
+            #[cfg(feature = "debug-names")]
+            name: None,
+            #[cfg(feature = "track-leaks")]
+            _tracked: crate::leaks::Tracked::new::<Self>(I::debug_id(&id)),
+        }
     }
 
     /// Try to create a new [`Runtime`] using the selected [`IdAlloc`] reusing ids
@@ -159,16 +285,104 @@ impl<I: IdAlloc, P: PoolMut<I::Id>> Runtime<I, P> {
     /// from the [`IdAlloc`]
     pub fn try_with_id_alloc_and_pool(id_alloc: &mut I, mut pool: P) -> Option<Self> {
         let id = match pool.take_mut() {
-            Some(id_alloc) => id_alloc.0,
-            None => id_alloc.try_alloc()?,
+            Some(id_alloc) => {
+                I::on_recycle(&id_alloc.0);
+                #[cfg(feature = "stats")]
+                stats::recycled::<I>();
+                id_alloc.0
+            }
+            None => {
+                let id = id_alloc.try_alloc()?;
+                #[cfg(feature = "stats")]
+                stats::fresh::<I>();
+                id
+            }
         };
 
-        Some(Runtime { id, pool })
+        Some(Runtime {
+            id,
+            pool,
+            #[cfg(feature = "debug-names")]
+            name: None,
+            #[cfg(feature = "track-leaks")]
+            _tracked: crate::leaks::Tracked::new::<Self>(I::debug_id(&id)),
+        })
     }
 
     #[inline]
     /// A handle that this [`Runtime`] identifier owns
     pub fn handle(&self) -> RuntimeHandle<I> { RuntimeHandle(self.id) }
+
+    /// Give this [`Runtime`] a name that shows up in the `Debug` output of it and its handles,
+    /// replacing any previous name
+    ///
+    /// The name is forgotten when the [`Runtime`] is dropped
+    #[cfg(feature = "debug-names")]
+    #[cfg_attr(doc, doc(cfg(feature = "debug-names")))]
+    pub fn named(mut self, name: &'static str) -> Self
+    where
+        I::Id: fmt::Debug,
+    {
+        self.name = None;
+        self.name = Some(names::Name::new(&self.id, name));
+        self
+    }
+
+    /// Swap in a new id from the given [`IdAlloc`], so that this [`Runtime`] no longer owns
+    /// any of the handles that it created before
+    ///
+    /// The new id always comes from the [`IdAlloc`], never from the pool, so it can't be the
+    /// id of any handle that is still around. The old id is discarded instead of being returned
+    /// to the pool, because handles with the old id may still be around, so no other [`Runtime`]
+    /// will ever own the old handles.
+    ///
+    /// This [`Runtime`] keeps its name (with `debug-names`). Because this takes `&mut self`,
+    /// it can't happen while the [`Runtime`] is borrowed, for example by a branded collection,
+    /// see the safety section of [`Identifier`](crate::Identifier).
+    ///
+    /// # Panic
+    ///
+    /// If there are no more ids, in which case this [`Runtime`] is unchanged
+    pub fn rekey_with_id_alloc(&mut self, id_alloc: &mut I) {
+        let id = id_alloc.alloc();
+        self.replace_id(id)
+    }
+
+    /// Try to swap in a new id from the given [`IdAlloc`], returns false and leaves this
+    /// [`Runtime`] unchanged if there are no more ids
+    ///
+    /// see [`Runtime::rekey_with_id_alloc`] for details
+    pub fn try_rekey_with_id_alloc(&mut self, id_alloc: &mut I) -> bool {
+        match id_alloc.try_alloc() {
+            Some(id) => {
+                self.replace_id(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn replace_id(&mut self, id: I::Id) {
+        #[cfg(feature = "stats")]
+        {
+            stats::dropped::<I>();
+            stats::fresh::<I>();
+        }
+        I::on_release(&self.id);
+        // the old id is never put back in the pool, since there may still be handles with that id
+        self.id = id;
+        #[cfg(feature = "debug-names")]
+        if let Some(name) = &mut self.name {
+            name.rekey(&self.id);
+        }
+        #[cfg(feature = "track-leaks")]
+        self._tracked.rekey(I::debug_id(&self.id));
+    }
+
+    /// Stop listing this [`Runtime`] in [`leaks::held`](crate::leaks::held), because it is
+    /// expected to be held for a long time, like the thread owner
+    #[cfg(feature = "track-leaks")]
+    pub(crate) fn untrack(&self) { self._tracked.untrack() }
 }
 
 impl<I: IdAlloc> Trivial for RuntimeHandle<I>
@@ -179,6 +393,8 @@ where
 }
 
 unsafe impl<I: IdAlloc> crate::Handle for RuntimeHandle<I> {}
+// This is safe because `RuntimeHandle` is `repr(transparent)`
+unsafe impl<I: IdAlloc + 'static> crate::any::Erasable for RuntimeHandle<I> where I::Id: crate::any::Erasable {}
 unsafe impl<I: IdAlloc, P: PoolMut<I::Id>> crate::Identifier for Runtime<I, P> {
     type Handle = RuntimeHandle<I>;
 
@@ -187,11 +403,86 @@ unsafe impl<I: IdAlloc, P: PoolMut<I::Id>> crate::Identifier for Runtime<I, P> {
 
     #[inline]
     fn owns(&self, handle: &Self::Handle) -> bool { self.id == handle.0 }
+
+    #[inline]
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError> {
+        if self.owns(handle) {
+            Ok(())
+        } else {
+            Err(mismatch::<I, Self>(&self.id, &handle.0))
+        }
+    }
+}
+
+forward_shared_identifier!([I: IdAlloc, P: PoolMut<I::Id>] Runtime<I, P>);
+
+/// The error for an identifier of type `S` with the id `ident` that doesn't own the handle with the id `handle`
+///
+/// this records both ids as a `Runtime` and a `RuntimeHandle` if the `IdAlloc` can show them
+#[cold]
+fn mismatch<I: IdAlloc, S: crate::SharedIdentifier>(ident: &I::Id, handle: &I::Id) -> OwnershipError {
+    let error = OwnershipError::new::<S>();
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    if let (Some(ident), Some(handle)) = (I::debug_id(ident), I::debug_id(handle)) {
+        return error.with_details(&FmtId(ident, fmt_runtime::<I>), &FmtId(handle, fmt_handle::<I>))
+    }
+
+    #[cfg(not(any(feature = "std", feature = "alloc")))]
+    let _ = (ident, handle);
+
+    error
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+type FmtIdFn = fn(&dyn fmt::Debug, &mut fmt::Formatter<'_>) -> fmt::Result;
+
+/// shows an id with `fmt_runtime` or `fmt_handle`
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct FmtId<'a>(&'a dyn fmt::Debug, FmtIdFn);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl fmt::Debug for FmtId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { (self.1)(self.0, f) }
+}
+
+// `I` is only used to look up the name of the id
+#[cfg_attr(not(feature = "debug-names"), allow(clippy::extra_unused_type_parameters))]
+fn fmt_runtime<I: IdAlloc>(id: &dyn fmt::Debug, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    #[cfg(feature = "debug-names")]
+    if let Some(name) = names::get::<I::Id>(id) {
+        return write!(f, "Runtime({:?}, {:?})", name, id)
+    }
+
+    write!(f, "Runtime({:?})", id)
+}
+
+// `I` is only used to look up the name of the id
+#[cfg_attr(not(feature = "debug-names"), allow(clippy::extra_unused_type_parameters))]
+fn fmt_handle<I: IdAlloc>(id: &dyn fmt::Debug, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut f = f.debug_struct("RuntimeHandle");
+    #[cfg(feature = "debug-names")]
+    if let Some(name) = names::get::<I::Id>(id) {
+        f.field("name", &name);
+    }
+    f.field("inner", id).finish()
+}
+
+// This is safe because a `Runtime` without a pool always gets a new id from the `IdAlloc`,
+// and drops its id instead of putting it in a pool, so its id is never used again
+unsafe impl<I: IdAlloc> crate::OneShotIdentifier for Runtime<I> {}
+
 impl<I: IdAlloc, P: PoolMut<I::Id>> Drop for Runtime<I, P> {
     #[inline]
-    fn drop(&mut self) { let _ = self.pool.try_put_mut(RuntimeId(self.id)); }
+    fn drop(&mut self) {
+        // forget the name before the id can be reused
+        #[cfg(feature = "debug-names")]
+        drop(self.name.take());
+        #[cfg(feature = "stats")]
+        stats::dropped::<I>();
+        I::on_release(&self.id);
+        let _ = self.pool.try_put_mut(RuntimeId(self.id));
+    }
 }
 
 impl<I: IdAlloc, P: PoolMut<I::Id>> Eq for Runtime<I, P> {}
@@ -204,7 +495,7 @@ impl<I: IdAlloc, P: PoolMut<I::Id>> fmt::Debug for Runtime<I, P>
 where
     I::Id: fmt::Debug,
 {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "Runtime({:?})", self.id) }
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt_runtime::<I>(&self.id, f) }
 }
 
 use core::{
@@ -212,15 +503,13 @@ use core::{
     hash::{Hash, Hasher},
 };
 
-use crate::Trivial;
+use crate::{OwnershipError, Trivial};
 
 impl<I: IdAlloc> fmt::Debug for RuntimeHandle<I>
 where
     I::Id: fmt::Debug,
 {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("RuntimeHandle").field("inner", &self.0).finish()
-    }
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt_handle::<I>(&self.0, f) }
 }
 
 impl<I: IdAlloc> Copy for RuntimeHandle<I> {}
@@ -0,0 +1,241 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use super::{Global, IdAlloc, Runtime, RuntimeHandle};
+
+/// A [`Runtime`] identifier that was created under a parent identifier
+///
+/// A child borrows its parent, so the parent can create many children, and children can
+/// create children of their own, like a document with sections that have paragraphs.
+/// Handles of a child remember all of their ancestors, so every ancestor can check if it
+/// [owns a descendant](Runtime::owns_descendant) of a handle, but a child never owns the
+/// handles of its ancestors.
+///
+/// ```
+/// use pui::{runtime::Runtime, Identifier};
+///
+/// let document = Runtime::new();
+/// let section = document.child();
+/// let paragraph = section.child();
+///
+/// assert!(document.owns_descendant(&paragraph.handle()));
+/// assert!(section.owns_descendant(&paragraph.handle()));
+/// assert!(!paragraph.owns_descendant(&section.handle()));
+///
+/// // the handles are only *owned* by the identifier that created them
+/// let sibling = document.child();
+/// assert!(section.owns(&section.handle()));
+/// assert!(!sibling.owns(&section.handle()));
+/// ```
+///
+/// `owns_descendant` doesn't prove exclusive access to the descendant's data on its own,
+/// because a child can be used while its parent is borrowed. But while you have an
+/// `&mut` to an ancestor, none of its descendants are alive, so a collection may hand out
+/// unique access to data branded by any descendant if it is given an `&mut` to an ancestor
+/// that `owns_descendant` the handle.
+///
+/// A child always gets a new id from the [`IdAlloc`], it never reuses ids from a pool.
+/// Only a [`Runtime`] without a pool can have children or check `owns_descendant`, because
+/// once a pooled parent is dropped, another [`Runtime`] could reuse its id, and would then
+/// own the descendants of the old parent's handles.
+pub struct ChildRuntime<'p, I: IdAlloc = Global, H = RuntimeHandle<I>> {
+    runtime: Runtime<I>,
+    parent: H,
+    lifetime: PhantomData<&'p ()>,
+}
+
+/// A handle to a [`ChildRuntime`] identifier, which also holds the handle of its parent
+pub struct ChildHandle<I: IdAlloc = Global, H = RuntimeHandle<I>> {
+    id: RuntimeHandle<I>,
+    parent: H,
+}
+
+/// A handle that knows the ids of the identifiers it descends from,
+/// see [`Runtime::owns_descendant`]
+///
+/// # Safety
+///
+/// `descends_from` must only return true if the identifier with the given id, or one of
+/// its descendants, owns this handle. Collections may rely on this to hand out unique
+/// access to data branded by a descendant, see [`ChildRuntime`].
+pub unsafe trait Lineage<I: IdAlloc> {
+    /// Check if this handle was created by the identifier with the given id,
+    /// or by one of its descendants
+    fn descends_from(&self, id: &I::Id) -> bool;
+}
+
+// This is safe because a `RuntimeHandle` is owned by the `Runtime` with the same id
+unsafe impl<I: IdAlloc> Lineage<I> for RuntimeHandle<I> {
+    #[inline]
+    fn descends_from(&self, id: &I::Id) -> bool { self.0 == *id }
+}
+
+// This is safe because a `ChildHandle` is owned by the `ChildRuntime` with the same id, and
+// `parent` can only be set by `ChildRuntime`, to the handle of that child's parent
+unsafe impl<I: IdAlloc, H: Lineage<I>> Lineage<I> for ChildHandle<I, H> {
+    #[inline]
+    fn descends_from(&self, id: &I::Id) -> bool { self.id.0 == *id || self.parent.descends_from(id) }
+}
+
+// Only runtimes without a pool can have children, because a pooled runtime that reused the
+// id of a dropped parent would own the descendants of the old parent's handles
+impl<I: IdAlloc> Runtime<I> {
+    /// Create a child of this [`Runtime`] using the selected [`IdAlloc`]
+    ///
+    /// see [`ChildRuntime`] for details
+    pub fn child_with_id_alloc(&self, id_alloc: &mut I) -> ChildRuntime<'_, I> {
+        ChildRuntime::new(Runtime::with_id_alloc(id_alloc), self.handle())
+    }
+
+    /// Try to create a child of this [`Runtime`] using the selected [`IdAlloc`],
+    /// returns `None` if there are no more ids
+    pub fn try_child_with_id_alloc(&self, id_alloc: &mut I) -> Option<ChildRuntime<'_, I>> {
+        Some(ChildRuntime::new(Runtime::try_with_id_alloc(id_alloc)?, self.handle()))
+    }
+
+    /// Check if the given handle was created by this [`Runtime`], or by one of its
+    /// descendants
+    ///
+    /// see [`ChildRuntime`] for details
+    #[inline]
+    pub fn owns_descendant<H: Lineage<I>>(&self, handle: &H) -> bool { handle.descends_from(&self.id) }
+}
+
+impl Runtime {
+    /// Create a child of this [`Runtime`] using [`Global`]
+    ///
+    /// see [`ChildRuntime`] for details
+    pub fn child(&self) -> ChildRuntime<'_> { self.child_with_id_alloc(&mut Global) }
+}
+
+impl<'p, I: IdAlloc, H: Clone> ChildRuntime<'p, I, H> {
+    fn new(runtime: Runtime<I>, parent: H) -> Self {
+        Self {
+            runtime,
+            parent,
+            lifetime: PhantomData,
+        }
+    }
+
+    /// A handle that this [`ChildRuntime`] identifier owns
+    #[inline]
+    pub fn handle(&self) -> ChildHandle<I, H> {
+        ChildHandle {
+            id: self.runtime.handle(),
+            parent: self.parent.clone(),
+        }
+    }
+
+    /// The handle of the parent identifier
+    #[inline]
+    pub fn parent(&self) -> &H { &self.parent }
+
+    /// Create a child of this [`ChildRuntime`] using the selected [`IdAlloc`]
+    pub fn child_with_id_alloc(&self, id_alloc: &mut I) -> ChildRuntime<'_, I, ChildHandle<I, H>> {
+        ChildRuntime::new(Runtime::with_id_alloc(id_alloc), self.handle())
+    }
+
+    /// Try to create a child of this [`ChildRuntime`] using the selected [`IdAlloc`],
+    /// returns `None` if there are no more ids
+    pub fn try_child_with_id_alloc(&self, id_alloc: &mut I) -> Option<ChildRuntime<'_, I, ChildHandle<I, H>>> {
+        Some(ChildRuntime::new(Runtime::try_with_id_alloc(id_alloc)?, self.handle()))
+    }
+
+    /// Check if the given handle was created by this [`ChildRuntime`], or by one of its
+    /// descendants
+    #[inline]
+    pub fn owns_descendant<L: Lineage<I>>(&self, handle: &L) -> bool { self.runtime.owns_descendant(handle) }
+}
+
+impl<'p, H: Clone> ChildRuntime<'p, Global, H> {
+    /// Create a child of this [`ChildRuntime`] using [`Global`]
+    pub fn child(&self) -> ChildRuntime<'_, Global, ChildHandle<Global, H>> { self.child_with_id_alloc(&mut Global) }
+}
+
+impl<I: IdAlloc, H> ChildHandle<I, H> {
+    /// The handle of the parent identifier
+    #[inline]
+    pub fn parent(&self) -> &H { &self.parent }
+}
+
+// This is safe because the `Runtime` that a `ChildRuntime` holds is the only identifier
+// with its id, and `ChildHandle` is `Send`/`Sync` only if both its id and parent handle are
+unsafe impl<I: IdAlloc, H: crate::Handle> crate::Identifier for ChildRuntime<'_, I, H> {
+    type Handle = ChildHandle<I, H>;
+
+    #[inline]
+    fn handle(&self) -> Self::Handle { self.handle() }
+
+    #[inline]
+    fn owns(&self, handle: &Self::Handle) -> bool { self.runtime.id == handle.id.0 }
+
+    #[inline]
+    fn check(&self, handle: &Self::Handle) -> Result<(), crate::OwnershipError> {
+        if self.owns(handle) {
+            Ok(())
+        } else {
+            Err(super::mismatch::<I, Self>(&self.runtime.id, &handle.id.0))
+        }
+    }
+}
+
+forward_shared_identifier!([I: IdAlloc, H: crate::Handle] ChildRuntime<'_, I, H>);
+
+unsafe impl<I: IdAlloc, H: crate::Handle> crate::Handle for ChildHandle<I, H> {}
+
+impl<I: IdAlloc, H> Eq for ChildRuntime<'_, I, H> {}
+impl<I: IdAlloc, H> PartialEq for ChildRuntime<'_, I, H> {
+    fn eq(&self, other: &Self) -> bool { self.runtime == other.runtime }
+}
+
+impl<I: IdAlloc, H: fmt::Debug> fmt::Debug for ChildRuntime<'_, I, H>
+where
+    I::Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildRuntime")
+            .field("runtime", &self.runtime)
+            .field("parent", &self.parent)
+            .finish()
+    }
+}
+
+impl<I: IdAlloc, H: fmt::Debug> fmt::Debug for ChildHandle<I, H>
+where
+    I::Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildHandle")
+            .field("id", &self.id)
+            .field("parent", &self.parent)
+            .finish()
+    }
+}
+
+impl<I: IdAlloc, H: Copy> Copy for ChildHandle<I, H> {}
+impl<I: IdAlloc, H: Clone> Clone for ChildHandle<I, H> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            parent: self.parent.clone(),
+        }
+    }
+}
+
+impl<I: IdAlloc, H: Eq> Eq for ChildHandle<I, H> {}
+impl<I: IdAlloc, H: PartialEq> PartialEq for ChildHandle<I, H> {
+    fn eq(&self, other: &Self) -> bool { self.id == other.id && self.parent == other.parent }
+}
+
+impl<I: IdAlloc, H: Hash> Hash for ChildHandle<I, H>
+where
+    I::Id: Hash,
+{
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.id.hash(state);
+        self.parent.hash(state);
+    }
+}
@@ -0,0 +1,175 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+use std::{
+    collections::HashSet,
+    sync::{Mutex, MutexGuard},
+};
+
+use super::{Global, IdAlloc, Runtime, RuntimeHandle};
+use crate::any::{AnyHandle, Erasable};
+
+/// An [`IdAlloc`] that knows which of its ids are still owned by a [`Runtime`](super::Runtime)
+///
+/// This allows you to check if the owner of a [`RuntimeHandle`] was dropped with
+/// [`RuntimeHandle::is_alive`], for example to purge the entries of a cache that
+/// is keyed by handles. Allocators made by [`make_global_id_alloc`](crate::make_global_id_alloc)
+/// implement this if you opt in with `track_liveness;`, every other allocator doesn't pay for it.
+///
+/// ```
+/// use pui::runtime::Runtime;
+///
+/// pui::make_global_id_alloc! {
+///     type MyIdAlloc(MyId) = u32;
+///     track_liveness;
+/// }
+///
+/// let runtime = MyIdAlloc::new();
+/// let handle = runtime.handle();
+///
+/// assert!(handle.is_alive());
+/// drop(runtime);
+/// assert!(!handle.is_alive());
+/// ```
+///
+/// Note that if the [`Runtime`](super::Runtime) used a pool, its id may be reused by
+/// another [`Runtime`](super::Runtime), and then the old handles are alive again,
+/// because they are owned by the new [`Runtime`](super::Runtime).
+pub trait TrackLiveness: IdAlloc {
+    /// Check if `id` was allocated or taken from a pool, and isn't released yet
+    fn is_alive(id: &Self::Id) -> bool;
+}
+
+impl<I: TrackLiveness> RuntimeHandle<I> {
+    /// Check if some [`Runtime`](super::Runtime) that owns this handle is still alive
+    ///
+    /// This may not be the [`Runtime`](super::Runtime) that created the handle,
+    /// if that one used a pool, see [`TrackLiveness`] for details
+    #[inline]
+    pub fn is_alive(&self) -> bool { I::is_alive(&self.0) }
+}
+
+/// A weak reference to a [`Runtime`], which can check if the [`Runtime`] is still alive
+///
+/// Unlike the [`Runtime`], this doesn't own any handles, so it can be freely copied and
+/// stored, for example next to a cache that should be purged once the [`Runtime`] is dropped.
+///
+/// ```
+/// use pui::runtime::Runtime;
+///
+/// pui::make_global_id_alloc! {
+///     type MyIdAlloc(MyId) = u32;
+///     track_liveness;
+/// }
+///
+/// let runtime = MyIdAlloc::new();
+/// let weak = runtime.downgrade();
+///
+/// assert!(weak.is_alive());
+/// assert_eq!(weak.handle(), runtime.handle());
+/// drop(runtime);
+/// assert!(!weak.is_alive());
+/// ```
+///
+/// see [`TrackLiveness`] for details
+pub struct WeakRuntime<I: IdAlloc = Global>(I::Id);
+
+impl<I: TrackLiveness> Runtime<I> {
+    /// Create a [`WeakRuntime`] that can check if this [`Runtime`] is still alive
+    ///
+    /// Only a [`Runtime`] without a pool can be downgraded, because the id of a pooled
+    /// [`Runtime`] may be reused by an unrelated [`Runtime`] once it is dropped.
+    #[inline]
+    pub fn downgrade(&self) -> WeakRuntime<I> { WeakRuntime(self.id) }
+}
+
+impl<I: TrackLiveness> WeakRuntime<I> {
+    /// Check if the [`Runtime`] is still alive
+    ///
+    /// see [`TrackLiveness`] for details
+    #[inline]
+    pub fn is_alive(&self) -> bool { I::is_alive(&self.0) }
+
+    /// The handle that the [`Runtime`] owns, or owned before it was dropped
+    #[inline]
+    pub fn handle(&self) -> RuntimeHandle<I> { RuntimeHandle(self.0) }
+}
+
+impl<I: IdAlloc> fmt::Debug for WeakRuntime<I>
+where
+    I::Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.debug_tuple("WeakRuntime").field(&self.0).finish() }
+}
+
+impl<I: IdAlloc> Copy for WeakRuntime<I> {}
+impl<I: IdAlloc> Clone for WeakRuntime<I> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<I: IdAlloc> Eq for WeakRuntime<I> {}
+impl<I: IdAlloc> PartialEq for WeakRuntime<I> {
+    fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+}
+
+impl<I: IdAlloc> Hash for WeakRuntime<I>
+where
+    I::Id: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
+/// The set of live ids of an [`IdAlloc`], which can be used to implement [`TrackLiveness`]
+///
+/// The ids should be inserted when they are allocated, and in [`IdAlloc::on_recycle`],
+/// and removed in [`IdAlloc::on_release`].
+pub struct LivenessSet<I> {
+    // the `HashSet` is created on first use, because it can't be created in a `const fn`
+    live: Mutex<Option<HashSet<AnyHandle>>>,
+    alloc: PhantomData<fn() -> I>,
+}
+
+impl<I> LivenessSet<I> {
+    /// Create a new empty set
+    pub const fn new() -> Self {
+        Self {
+            live: Mutex::new(None),
+            alloc: PhantomData,
+        }
+    }
+}
+
+impl<I> Default for LivenessSet<I> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<I: IdAlloc + 'static> LivenessSet<I>
+where
+    I::Id: Erasable,
+{
+    fn live(&self) -> MutexGuard<'_, Option<HashSet<AnyHandle>>> {
+        match self.live.lock() {
+            Ok(live) => live,
+            Err(poison) => poison.into_inner(),
+        }
+    }
+
+    fn key(id: &I::Id) -> AnyHandle { AnyHandle::new(RuntimeHandle::<I>(*id)) }
+
+    /// Mark `id` as alive
+    pub fn insert(&self, id: &I::Id) { self.live().get_or_insert_with(HashSet::new).insert(Self::key(id)); }
+
+    /// Mark `id` as released
+    pub fn remove(&self, id: &I::Id) {
+        if let Some(live) = &mut *self.live() {
+            live.remove(&Self::key(id));
+        }
+    }
+
+    /// Check if `id` is alive
+    pub fn contains(&self, id: &I::Id) -> bool {
+        self.live().as_ref().is_some_and(|live| live.contains(&Self::key(id)))
+    }
+}
@@ -25,9 +25,53 @@
 /// # let pool = ();
 /// let runtime_id_alloc /* : Runtime<MyIdAlloc, _> */ = MyIdAlloc::with_pool(pool);
 /// ```
+///
+/// You can also add an [`IdAllocObserver`](crate::runtime::IdAllocObserver),
+/// which is told about every id that is allocated, recycled, and released
+/// ```
+/// # struct MyObserver;
+/// # impl pui::runtime::IdAllocObserver<MyId> for MyObserver {}
+/// pui::make_global_id_alloc! {
+///     pub type MyIdAlloc(MyId) = [u8; 3];
+///     observer = MyObserver;
+/// }
+/// ```
+///
+/// With the `std` feature, you can opt into tracking which ids are still owned by a `Runtime`,
+/// which implements [`TrackLiveness`](crate::runtime::TrackLiveness)
+/// ```
+/// # #[cfg(feature = "std")]
+/// pui::make_global_id_alloc! {
+///     pub type MyIdAlloc(MyId) = [u8; 3];
+///     track_liveness;
+/// }
+/// ```
+/// `track_liveness;` goes after the observer, if you use both.
+///
+/// With the `stats` feature, `MyIdAlloc::stats()` returns the
+/// [`IdAllocStats`](crate::runtime::IdAllocStats) for the `Runtime`s created from it.
 #[macro_export]
 macro_rules! make_global_id_alloc {
     ($(#[$meta:meta])*$v:vis type $name:ident($(#[$id_meta:meta])* $id:ident) = $inner:ty;) => {
+        $crate::make_global_id_alloc! {
+            $(#[$meta])*
+            $v type $name($(#[$id_meta])* $id) = $inner;
+            observer = ();
+        }
+    };
+    ($(#[$meta:meta])*$v:vis type $name:ident($(#[$id_meta:meta])* $id:ident) = $inner:ty; track_liveness;) => {
+        $crate::make_global_id_alloc! {
+            $(#[$meta])*
+            $v type $name($(#[$id_meta])* $id) = $inner;
+            observer = ();
+            track_liveness;
+        }
+    };
+    (
+        $(#[$meta:meta])*$v:vis type $name:ident($(#[$id_meta:meta])* $id:ident) = $inner:ty;
+        observer = $observer:ty;
+        $($track_liveness:ident;)?
+    ) => {
         $(#[$meta])*
         $v struct $name;
 
@@ -35,6 +79,9 @@ macro_rules! make_global_id_alloc {
         #[derive(Debug, Clone, Copy, PartialEq, Eq)]
         $v struct $id($inner);
 
+        // This is safe because `$id` only contains a `Scalar`, which is `Erasable`
+        unsafe impl $crate::any::Erasable for $id {}
+
         impl $name {
             /// Create a new new `Runtime`
             ///
@@ -60,10 +107,18 @@ macro_rules! make_global_id_alloc {
             pub fn try_with_pool<P: $crate::runtime::PoolMut<$id>>(pool: P) -> Option<$crate::runtime::Runtime<Self, P>> {
                 $crate::runtime::Runtime::try_with_id_alloc_and_pool(&mut Self, pool)
             }
+
+            $crate::make_global_id_alloc_stats!(inherent $inner);
+            $crate::make_global_id_alloc_liveness!(inherent $name $($track_liveness)?);
         }
+
+        $crate::make_global_id_alloc_liveness!(trait $name $($track_liveness)?);
+
         unsafe impl $crate::runtime::IdAlloc for $name {
             type Id = $id;
 
+            $crate::make_global_id_alloc_stats!(trait);
+
             fn alloc(&mut self) -> $id {
                 <Self as $crate::runtime::IdAlloc>::try_alloc(self).expect($crate::macros::concat!(
                     "Cannot overflow <",
@@ -77,12 +132,122 @@ macro_rules! make_global_id_alloc {
                 static make_runtime_NEXT_ID: <$inner as $crate::macros::Scalar>::Atomic =
                     <$inner as $crate::macros::Scalar>::INIT_ATOMIC;
 
-                <$inner as $crate::macros::Scalar>::inc_atomic(&make_runtime_NEXT_ID).map($id)
+                match <$inner as $crate::macros::Scalar>::inc_atomic(&make_runtime_NEXT_ID) {
+                    $crate::macros::Some(id) => {
+                        let id = $id(id);
+                        $crate::make_global_id_alloc_stats!(alloc);
+                        $crate::make_global_id_alloc_liveness!(insert id $($track_liveness)?);
+                        <$observer as $crate::runtime::IdAllocObserver<$id>>::on_alloc(&id);
+                        $crate::macros::Some(id)
+                    }
+                    $crate::macros::None => {
+                        <$observer as $crate::runtime::IdAllocObserver<$id>>::on_exhausted();
+                        $crate::macros::None
+                    }
+                }
             }
+
+            #[inline]
+            fn on_recycle(id: &$id) {
+                $crate::make_global_id_alloc_liveness!(insert id $($track_liveness)?);
+                <$observer as $crate::runtime::IdAllocObserver<$id>>::on_recycle(id)
+            }
+
+            #[inline]
+            fn on_release(id: &$id) {
+                $crate::make_global_id_alloc_liveness!(remove id $($track_liveness)?);
+                <$observer as $crate::runtime::IdAllocObserver<$id>>::on_release(id)
+            }
+
+            #[inline]
+            fn debug_id(id: &$id) -> $crate::macros::Option<&dyn $crate::macros::Debug> { $crate::macros::Some(id) }
         }
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "stats")]
+macro_rules! make_global_id_alloc_stats {
+    (inherent $inner:ty) => {
+        /// Statistics about the `Runtime`s created from this id_alloc
+        pub fn stats() -> &'static $crate::runtime::IdAllocStats {
+            #[allow(non_upper_case_globals)]
+            static make_runtime_STATS: $crate::runtime::IdAllocStats =
+                $crate::runtime::IdAllocStats::new(<$inner as $crate::macros::Scalar>::CAPACITY);
+
+            &make_runtime_STATS
+        }
+    };
+    (trait) => {
+        #[inline]
+        fn stats() -> $crate::macros::Option<&'static $crate::runtime::IdAllocStats> {
+            $crate::macros::Some(Self::stats())
+        }
+    };
+    (alloc) => {
+        Self::stats().record_alloc()
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "stats"))]
+macro_rules! make_global_id_alloc_stats {
+    (inherent $inner:ty) => {};
+    (trait) => {};
+    (alloc) => {};
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! make_global_id_alloc_liveness {
+    (inherent $name:ident) => {};
+    (inherent $name:ident track_liveness) => {
+        #[doc(hidden)]
+        pub fn make_runtime_liveness() -> &'static $crate::runtime::LivenessSet<Self> {
+            #[allow(non_upper_case_globals)]
+            static make_runtime_LIVENESS: $crate::runtime::LivenessSet<$name> = $crate::runtime::LivenessSet::new();
+
+            &make_runtime_LIVENESS
+        }
+    };
+    (trait $name:ident) => {};
+    (trait $name:ident track_liveness) => {
+        impl $crate::runtime::TrackLiveness for $name {
+            #[inline]
+            fn is_alive(id: &<Self as $crate::runtime::IdAlloc>::Id) -> bool {
+                Self::make_runtime_liveness().contains(id)
+            }
+        }
+    };
+    (insert $id:ident) => {};
+    (insert $id:ident track_liveness) => {
+        Self::make_runtime_liveness().insert(&$id)
+    };
+    (remove $id:ident) => {};
+    (remove $id:ident track_liveness) => {
+        Self::make_runtime_liveness().remove($id)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "std"))]
+macro_rules! make_global_id_alloc_liveness {
+    (inherent $name:ident) => {};
+    (inherent $name:ident track_liveness) => {
+        $crate::macros::compile_error! {"the `std` feature on `pui` must be turned on to allow `track_liveness`"}
+    };
+    (trait $name:ident) => {};
+    (trait $name:ident track_liveness) => {};
+    (insert $id:ident) => {};
+    (insert $id:ident track_liveness) => {};
+    (remove $id:ident) => {};
+    (remove $id:ident track_liveness) => {};
+}
+
 /// Create a new type that implements [`IdAlloc`](crate::runtime::IdAlloc)
 /// that can be used with [`Runtime`](crate::runtime::Runtime)
 /// which is implemented using a thread-local count
@@ -187,44 +352,45 @@ macro_rules! make_global_id_alloc_tl {
 #[macro_export]
 macro_rules! make_global_option_pool {
     ($(#[$meta:meta])* $v:vis one $name:ident($item:ty);) => {
+        $crate::make_global_option_pool!{$(#[$meta])* $v slots(1) $name($item);}
+    };
+    ($(#[$meta:meta])* $v:vis slots($slots:expr) $name:ident($item:ty);) => {
         $(#[$meta])*
         #[derive(Clone, Copy)]
         $v struct $name;
 
         const _: () = {
+            #[allow(non_upper_case_globals, clippy::declare_interior_mutable_const)]
+            const make_global_EMPTY_SLOT: $crate::macros::AtomicSlot<$crate::runtime::RuntimeId<$item>> =
+                $crate::macros::AtomicSlot::new();
             #[allow(non_upper_case_globals)]
-            static make_global_option_pool: $crate::macros::InitFlag = $crate::macros::InitFlag::new();
-            #[allow(non_upper_case_globals)]
-            static mut make_global_SINGLE_REUSE_ITEM:
-                $crate::macros::MaybeUninit<$crate::runtime::RuntimeId<$item>> = $crate::macros::MaybeUninit::uninit();
+            static make_global_SLOTS: [$crate::macros::AtomicSlot<$crate::runtime::RuntimeId<$item>>; $slots] =
+                [make_global_EMPTY_SLOT; $slots];
 
             impl $crate::runtime::PoolMut<$item> for $name {
-                fn try_put_mut(&mut self, value: $crate::runtime::RuntimeId<$item>) -> Result<(), $crate::runtime::RuntimeId<$item>> {
-                    if make_global_option_pool.start_init() {
-                        unsafe { make_global_SINGLE_REUSE_ITEM = $crate::macros::MaybeUninit::new(value); }
-                        make_global_option_pool.finish_init();
-                        Ok(())
-                    } else {
-                        Err(value)
+                fn try_put_mut(&mut self, mut value: $crate::runtime::RuntimeId<$item>) -> Result<(), $crate::runtime::RuntimeId<$item>> {
+                    for slot in make_global_SLOTS.iter() {
+                        match slot.try_put(value) {
+                            Ok(()) => return Ok(()),
+                            Err(v) => value = v,
+                        }
                     }
+
+                    Err(value)
                 }
 
                 fn take_mut(&mut self) -> Option<$crate::runtime::RuntimeId<$item>> {
-                    if make_global_option_pool.start_take() {
-                        let x = unsafe { make_global_SINGLE_REUSE_ITEM.as_ptr().read() };
-                        make_global_option_pool.finish_take();
-                        Some(x)
-                    } else {
-                        None
-                    }
+                    make_global_SLOTS.iter().find_map($crate::macros::AtomicSlot::take)
                 }
             }
 
             impl $crate::runtime::Pool<$item> for $name {
+                #[inline]
                 fn try_put(&self, value: $crate::runtime::RuntimeId<$item>) -> Result<(), $crate::runtime::RuntimeId<$item>> {
                     <Self as $crate::runtime::PoolMut<$item>>::try_put_mut(&mut $name, value)
                 }
 
+                #[inline]
                 fn take(&self) -> Option<$crate::runtime::RuntimeId<$item>> {
                     <Self as $crate::runtime::PoolMut<$item>>::take_mut(&mut $name)
                 }
@@ -247,18 +413,21 @@ macro_rules! make_global_option_pool {
 
             impl $crate::runtime::PoolMut<$item> for $name {
                 fn try_put_mut(&mut self, value: $crate::runtime::RuntimeId<$item>) -> Result<(), $crate::runtime::RuntimeId<$item>> {
-                    make_global_SINGLE_REUSE_ITEM.with(|c| {
+                    // the pool may already be destroyed if this is called while the thread exits
+                    let mut value = Some(value);
+                    let _ = make_global_SINGLE_REUSE_ITEM.try_with(|c| {
                         if unsafe { (*c.as_ptr()).is_none() } {
-                            c.set(Some(value));
-                            Ok(())
-                        } else {
-                            Err(value)
+                            c.set(value.take());
                         }
-                    })
+                    });
+                    match value {
+                        None => Ok(()),
+                        Some(value) => Err(value),
+                    }
                 }
 
                 fn take_mut(&mut self) -> Option<$crate::runtime::RuntimeId<$item>> {
-                    make_global_SINGLE_REUSE_ITEM.with(|c| c.take())
+                    make_global_SINGLE_REUSE_ITEM.try_with(|c| c.take()).unwrap_or(None)
                 }
             }
 
@@ -290,6 +459,9 @@ macro_rules! make_global_pool {
     ($(#[$meta:meta])* $v:vis one $name:ident($item:ty);) => {
         $crate::make_global_option_pool!{$(#[$meta])* $v one $name($item);}
     };
+    ($(#[$meta:meta])* $v:vis slots($slots:expr) $name:ident($item:ty);) => {
+        $crate::make_global_option_pool!{$(#[$meta])* $v slots($slots) $name($item);}
+    };
     ($(#[$meta:meta])* $v:vis thread_local stack $name:ident($item:ty);) => {
         $crate::macros::compile_error!{"the `std` feature on `pui` must be turned on to allow thread local stack pool"}
     };
@@ -324,6 +496,7 @@ macro_rules! make_global_pool {
 /// * queue - FIFO order
 /// * thread_local queue - FIFO order, but stores ids in a thread local (this is best used with thread local ids)
 /// * one - stores a single id, best used with a id_alloc backed by `()`
+/// * slots(N) - stores up to `N` ids, in no particular order
 /// * thread_local one - stores a single id, best used with a thread_local id backed by `()`
 ///
 /// in place of `pui::runtime::Global` you can use any type that implements `IdAlloc`
@@ -338,16 +511,10 @@ macro_rules! make_global_pool {
         const _: () = {
             #[allow(non_upper_case_globals)]
             fn make_global_get_it() -> $crate::macros::MutexGuard<'static, $crate::macros::Vec<$crate::runtime::RuntimeId<$item>>> {
-                static mut make_global_REUSE: $crate::macros::MaybeUninit<$crate::macros::Mutex<$crate::macros::Vec<$crate::runtime::RuntimeId<$item>>>> =
-                    $crate::macros::MaybeUninit::uninit();
-                static make_global_ONCE: $crate::macros::Once = $crate::macros::Once::new();
+                static make_global_REUSE: $crate::macros::GlobalCell<$crate::macros::Mutex<$crate::macros::Vec<$crate::runtime::RuntimeId<$item>>>> =
+                    $crate::macros::GlobalCell::new();
 
-                make_global_ONCE.call_once(|| unsafe {
-                    make_global_REUSE = $crate::macros::MaybeUninit::new($crate::macros::Mutex::default());
-                });
-
-                let make_global = unsafe { &*make_global_REUSE.as_ptr() };
-                make_global.lock().unwrap()
+                make_global_REUSE.get_or_init($crate::macros::Mutex::default).lock().unwrap()
             }
 
             impl $crate::runtime::PoolMut<$item> for $name {
@@ -382,16 +549,10 @@ macro_rules! make_global_pool {
         const _: () = {
             #[allow(non_upper_case_globals)]
             fn make_global_get_it() -> $crate::macros::MutexGuard<'static, $crate::macros::VecDeque<$crate::runtime::RuntimeId<$item>>> {
-                static mut make_global_REUSE: $crate::macros::MaybeUninit<$crate::macros::Mutex<$crate::macros::VecDeque<$crate::runtime::RuntimeId<$item>>>> =
-                    $crate::macros::MaybeUninit::uninit();
-                static make_global_ONCE: $crate::macros::Once = $crate::macros::Once::new();
-
-                make_global_ONCE.call_once(|| unsafe {
-                    make_global_REUSE = $crate::macros::MaybeUninit::new($crate::macros::Mutex::default());
-                });
+                static make_global_REUSE: $crate::macros::GlobalCell<$crate::macros::Mutex<$crate::macros::VecDeque<$crate::runtime::RuntimeId<$item>>>> =
+                    $crate::macros::GlobalCell::new();
 
-                let make_global = unsafe { &*make_global_REUSE.as_ptr() };
-                make_global.lock().unwrap()
+                make_global_REUSE.get_or_init($crate::macros::Mutex::default).lock().unwrap()
             }
 
             impl $crate::runtime::PoolMut<$item> for $name {
@@ -421,6 +582,9 @@ macro_rules! make_global_pool {
     ($(#[$meta:meta])* $v:vis one $name:ident($item:ty);) => {
         $crate::make_global_option_pool!{$(#[$meta])* $v one $name($item);}
     };
+    ($(#[$meta:meta])* $v:vis slots($slots:expr) $name:ident($item:ty);) => {
+        $crate::make_global_option_pool!{$(#[$meta])* $v slots($slots) $name($item);}
+    };
     ($(#[$meta:meta])* $v:vis thread_local stack $name:ident($item:ty);) => {
         $(#[$meta])*
         #[derive(Clone, Copy)]
@@ -437,16 +601,21 @@ macro_rules! make_global_pool {
 
             impl $crate::runtime::PoolMut<$item> for $name {
                 fn try_put_mut(&mut self, value: $crate::runtime::RuntimeId<$item>) -> Result<(), $crate::runtime::RuntimeId<$item>> {
-                    make_global_REUSE.with(|pool| unsafe {
-                        (&mut *pool.get()).push(value)
+                    // the pool may already be destroyed if this is called while the thread exits
+                    let mut value = Some(value);
+                    let _ = make_global_REUSE.try_with(|pool| unsafe {
+                        (&mut *pool.get()).push(value.take().unwrap())
                     });
-                    Ok(())
+                    match value {
+                        None => Ok(()),
+                        Some(value) => Err(value),
+                    }
                 }
 
                 fn take_mut(&mut self) -> Option<$crate::runtime::RuntimeId<$item>> {
-                    make_global_REUSE.with(|pool| unsafe {
+                    make_global_REUSE.try_with(|pool| unsafe {
                         (&mut *pool.get()).pop()
-                    })
+                    }).unwrap_or(None)
                 }
             }
 
@@ -479,16 +648,21 @@ macro_rules! make_global_pool {
 
             impl $crate::runtime::PoolMut<$item> for $name {
                 fn try_put_mut(&mut self, value: $crate::runtime::RuntimeId<$item>) -> Result<(), $crate::runtime::RuntimeId<$item>> {
-                    make_global_REUSE.with(|pool| unsafe {
-                        (&mut *pool.get()).push_back(value)
+                    // the pool may already be destroyed if this is called while the thread exits
+                    let mut value = Some(value);
+                    let _ = make_global_REUSE.try_with(|pool| unsafe {
+                        (&mut *pool.get()).push_back(value.take().unwrap())
                     });
-                    Ok(())
+                    match value {
+                        None => Ok(()),
+                        Some(value) => Err(value),
+                    }
                 }
 
                 fn take_mut(&mut self) -> Option<$crate::runtime::RuntimeId<$item>> {
-                    make_global_REUSE.with(|pool| unsafe {
+                    make_global_REUSE.try_with(|pool| unsafe {
                         (&mut *pool.get()).pop_front()
-                    })
+                    }).unwrap_or(None)
                 }
             }
 
@@ -0,0 +1,60 @@
+use core::{any::type_name, fmt};
+use std::{collections::HashMap, string::String, sync::Mutex};
+
+use crate::macros::GlobalCell;
+
+// the ids are keyed by their type name and debug representation, because `IdAlloc::Id`
+// doesn't need to be `Hash` or `'static`
+type Key = (&'static str, String);
+
+static NAMES: GlobalCell<Mutex<HashMap<Key, &'static str>>> = GlobalCell::new();
+
+fn names() -> std::sync::MutexGuard<'static, HashMap<Key, &'static str>> {
+    match NAMES.get_or_init(Mutex::default).lock() {
+        Ok(names) => names,
+        Err(poison) => poison.into_inner(),
+    }
+}
+
+fn key<T: fmt::Debug>(id: &T) -> Key { (type_name::<T>(), std::format!("{:?}", id)) }
+
+/// The registry entry for a named [`Runtime`](super::Runtime), which is removed on drop
+pub(super) struct Name<T> {
+    key: Key,
+    name: &'static str,
+    // captured in `new`, so that `rekey` doesn't need `T: Debug`
+    key_of: fn(&T) -> Key,
+}
+
+impl<T> Name<T> {
+    pub fn new(id: &T, name: &'static str) -> Self
+    where
+        T: fmt::Debug,
+    {
+        let key_of = key::<T>;
+        let key = key_of(id);
+        names().insert(key.clone(), name);
+        Self { key, name, key_of }
+    }
+
+    /// Move the name over to a new id
+    pub fn rekey(&mut self, id: &T) {
+        let key = (self.key_of)(id);
+        let mut names = names();
+        names.remove(&self.key);
+        names.insert(key.clone(), self.name);
+        self.key = key;
+    }
+}
+
+impl<T> Drop for Name<T> {
+    fn drop(&mut self) { names().remove(&self.key); }
+}
+
+/// The name of the runtime that owns `id`, if it was given one
+///
+/// `id` is passed as a `dyn Debug`, so that this also works for ids that are only
+/// shown through [`IdAlloc::debug_id`](super::IdAlloc::debug_id)
+pub(super) fn get<T>(id: &dyn fmt::Debug) -> Option<&'static str> {
+    names().get(&(type_name::<T>(), std::format!("{:?}", id))).copied()
+}
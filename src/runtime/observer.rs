@@ -0,0 +1,51 @@
+/// Hooks into the lifecycle of the ids of an [`IdAlloc`](super::IdAlloc)
+///
+/// You can plug an observer into [`make_global_id_alloc`](crate::make_global_id_alloc)
+/// with `observer = MyObserver;`. Every hook does nothing by default, and `()` is an observer
+/// that doesn't observe anything.
+///
+/// ```
+/// use pui::runtime::IdAllocObserver;
+/// use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+///
+/// static LIVE: AtomicUsize = AtomicUsize::new(0);
+///
+/// struct CountLive;
+///
+/// impl IdAllocObserver<MyId> for CountLive {
+///     fn on_alloc(_: &MyId) { LIVE.fetch_add(1, Relaxed); }
+///
+///     fn on_recycle(_: &MyId) { LIVE.fetch_add(1, Relaxed); }
+///
+///     fn on_release(_: &MyId) { LIVE.fetch_sub(1, Relaxed); }
+/// }
+///
+/// pui::make_global_id_alloc! {
+///     type MyIdAlloc(MyId) = u32;
+///     observer = CountLive;
+/// }
+///
+/// let runtime = MyIdAlloc::new();
+/// assert_eq!(LIVE.load(Relaxed), 1);
+/// drop(runtime);
+/// assert_eq!(LIVE.load(Relaxed), 0);
+/// ```
+pub trait IdAllocObserver<Id> {
+    /// Called after a new id was allocated
+    #[inline]
+    fn on_alloc(_id: &Id) {}
+
+    /// Called after a [`Runtime`](super::Runtime) reused an id from a pool
+    #[inline]
+    fn on_recycle(_id: &Id) {}
+
+    /// Called when a [`Runtime`](super::Runtime) is dropped, before its id is returned to the pool
+    #[inline]
+    fn on_release(_id: &Id) {}
+
+    /// Called when the allocator failed to allocate a new id, because it ran out of ids
+    #[inline]
+    fn on_exhausted() {}
+}
+
+impl<Id> IdAllocObserver<Id> for () {}
@@ -0,0 +1,108 @@
+use core::{
+    fmt,
+    hash::{BuildHasher, Hasher},
+};
+use std::{collections::hash_map::RandomState, sync::OnceLock};
+
+use super::{IdAlloc, PoolMut, Runtime};
+use crate::macros::Scalar;
+
+/// An allocator for [`Runtime`] ids, whose ids are unique but hard to predict
+///
+/// [`Global`](super::Global) and the allocators made by [`make_global_id_alloc`](crate::make_global_id_alloc)
+/// hand out ids in order, so anyone who can see one id can guess the others. `Randomized`
+/// counts just like them, but passes the count through a permutation of `u64` that is keyed
+/// by a random seed, which is picked once per process. Because the permutation is a bijection,
+/// the ids are still unique, but you can't guess other ids from the ones you see.
+///
+/// ```
+/// use pui::runtime::Randomized;
+///
+/// let a = Randomized::new();
+/// let b = Randomized::new();
+///
+/// assert_ne!(a.handle(), b.handle());
+/// ```
+///
+/// The permutation is a Feistel network whose round function is the `SipHash` of the
+/// standard library's [`RandomState`]. This resists guessing, but it isn't audited
+/// cryptography, so don't rely on ids staying secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Randomized;
+
+/// The id used by [`Randomized`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RandomizedId(u64);
+
+// This is safe because `RandomizedId` only contains a `u64`
+unsafe impl crate::any::Erasable for RandomizedId {}
+
+const ROUNDS: u64 = 8;
+
+// this uses `std`'s `OnceLock` instead of `GlobalCell`, because `GlobalCell::new` isn't a `const fn` under loom
+static SEED: OnceLock<RandomState> = OnceLock::new();
+
+fn round(seed: &RandomState, round: u64, half: u32) -> u32 {
+    let mut hasher = seed.build_hasher();
+    hasher.write_u64(round);
+    hasher.write_u32(half);
+    hasher.finish() as u32
+}
+
+/// A keyed permutation of `u64`
+fn permute(count: u64) -> u64 {
+    let seed = SEED.get_or_init(RandomState::new);
+    let mut left = (count >> 32) as u32;
+    let mut right = count as u32;
+
+    for i in 0..ROUNDS {
+        let next = left ^ round(seed, i, right);
+        left = right;
+        right = next;
+    }
+
+    (u64::from(left) << 32) | u64::from(right)
+}
+
+impl Randomized {
+    /// Create a new [`Runtime`] with a randomized id
+    ///
+    /// panics if there are no more ids
+    pub fn new() -> Runtime<Self> { Runtime::with_id_alloc(&mut Self) }
+
+    /// Try to create a new [`Runtime`] with a randomized id, returns `None` if there are no more ids
+    pub fn try_new() -> Option<Runtime<Self>> { Runtime::try_with_id_alloc(&mut Self) }
+
+    /// Create a new [`Runtime`] with the given pool
+    ///
+    /// panics if the pool is empty and there are no more ids
+    pub fn with_pool<P: PoolMut<RandomizedId>>(pool: P) -> Runtime<Self, P> {
+        Runtime::with_id_alloc_and_pool(&mut Self, pool)
+    }
+
+    /// Try to create a new [`Runtime`] with the given pool,
+    /// returns `None` if the pool is empty and there are no more ids
+    pub fn try_with_pool<P: PoolMut<RandomizedId>>(pool: P) -> Option<Runtime<Self, P>> {
+        Runtime::try_with_id_alloc_and_pool(&mut Self, pool)
+    }
+}
+
+// This is safe because the count never repeats, and `permute` is a bijection,
+// so the ids never repeat either
+unsafe impl IdAlloc for Randomized {
+    type Id = RandomizedId;
+
+    fn alloc(&mut self) -> Self::Id {
+        self.try_alloc()
+            .expect("Cannot overflow <Randomized as pui::runtime::IdAlloc>::next")
+    }
+
+    fn try_alloc(&mut self) -> Option<Self::Id> {
+        static NEXT: <u64 as Scalar>::Atomic = <u64 as Scalar>::INIT_ATOMIC;
+
+        <u64 as Scalar>::inc_atomic(&NEXT).map(|count| RandomizedId(permute(count)))
+    }
+
+    #[inline]
+    fn debug_id(id: &Self::Id) -> Option<&dyn fmt::Debug> { Some(id) }
+}
@@ -0,0 +1,139 @@
+use core::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+use super::IdAlloc;
+
+/// Statistics about the [`Runtime`](super::Runtime)s created from an [`IdAlloc`]
+///
+/// Most counters are only updated by [`Runtime`](super::Runtime), so ids allocated directly
+/// from the [`IdAlloc`] are not counted by them. The exception is [`allocated`](IdAllocStats::allocated),
+/// which the allocator updates itself, so [`remaining`](IdAllocStats::remaining) is accurate even if
+/// ids were allocated without a `Runtime`. All counters are updated with relaxed atomics,
+/// so they may be slightly out of date if other threads are creating `Runtime`s.
+///
+/// ```
+/// pui::make_global_id_alloc! {
+///     type MyIdAlloc(MyId) = u8;
+/// }
+///
+/// let a = MyIdAlloc::new();
+/// let b = MyIdAlloc::new();
+/// drop(a);
+///
+/// let stats = MyIdAlloc::stats();
+/// assert_eq!(stats.live(), 1);
+/// assert_eq!(stats.high_water_mark(), 2);
+/// assert_eq!(stats.fresh(), 2);
+/// assert_eq!(stats.remaining(), 253);
+///
+/// // allocating directly from the `IdAlloc` isn't a `Runtime`, but it still uses up an id
+/// let _id = pui::runtime::IdAlloc::alloc(&mut MyIdAlloc);
+/// assert_eq!(stats.fresh(), 2);
+/// assert_eq!(stats.allocated(), 3);
+/// assert_eq!(stats.remaining(), 252);
+/// ```
+pub struct IdAllocStats {
+    live: AtomicUsize,
+    high_water_mark: AtomicUsize,
+    fresh: AtomicUsize,
+    recycled: AtomicUsize,
+    allocated: AtomicUsize,
+    capacity: u128,
+}
+
+impl IdAllocStats {
+    /// Create a new set of statistics for an allocator that can hand out `capacity` ids
+    pub const fn new(capacity: u128) -> Self {
+        Self {
+            live: AtomicUsize::new(0),
+            high_water_mark: AtomicUsize::new(0),
+            fresh: AtomicUsize::new(0),
+            recycled: AtomicUsize::new(0),
+            allocated: AtomicUsize::new(0),
+            capacity,
+        }
+    }
+
+    /// The number of `Runtime`s that are currently alive
+    #[inline]
+    pub fn live(&self) -> usize { self.live.load(Relaxed) }
+
+    /// The largest number of `Runtime`s that were alive at the same time
+    #[inline]
+    pub fn high_water_mark(&self) -> usize { self.high_water_mark.load(Relaxed) }
+
+    /// The number of `Runtime`s that got a new id from the allocator
+    #[inline]
+    pub fn fresh(&self) -> usize { self.fresh.load(Relaxed) }
+
+    /// The number of `Runtime`s that reused an id from a pool
+    #[inline]
+    pub fn recycled(&self) -> usize { self.recycled.load(Relaxed) }
+
+    /// The number of ids that the allocator handed out, whether or not they were used by a `Runtime`
+    ///
+    /// This is only counted by allocators that call [`IdAllocStats::record_alloc`],
+    /// like the ones created by [`make_global_id_alloc`](crate::make_global_id_alloc)
+    #[inline]
+    pub fn allocated(&self) -> usize { self.allocated.load(Relaxed) }
+
+    /// Record that the allocator handed out a new id
+    ///
+    /// Allocators that return these statistics from [`IdAlloc::stats`] should call this
+    /// every time [`IdAlloc::try_alloc`] succeeds
+    #[inline]
+    pub fn record_alloc(&self) { self.allocated.fetch_add(1, Relaxed); }
+
+    /// The number of ids that the allocator can hand out in total
+    #[inline]
+    pub fn capacity(&self) -> u128 { self.capacity }
+
+    /// The number of ids that the allocator can still hand out before it's exhausted
+    ///
+    /// If the allocator doesn't call [`IdAllocStats::record_alloc`], then this
+    /// only subtracts the ids that were handed out to `Runtime`s
+    #[inline]
+    pub fn remaining(&self) -> u128 {
+        let used = self.allocated().max(self.fresh());
+        self.capacity.saturating_sub(used as u128)
+    }
+
+    fn created(&self, counter: &AtomicUsize) {
+        counter.fetch_add(1, Relaxed);
+        let live = self.live.fetch_add(1, Relaxed) + 1;
+        self.high_water_mark.fetch_max(live, Relaxed);
+    }
+}
+
+impl fmt::Debug for IdAllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdAllocStats")
+            .field("live", &self.live())
+            .field("high_water_mark", &self.high_water_mark())
+            .field("fresh", &self.fresh())
+            .field("recycled", &self.recycled())
+            .field("allocated", &self.allocated())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+pub(super) fn fresh<I: IdAlloc>() {
+    if let Some(stats) = I::stats() {
+        stats.created(&stats.fresh)
+    }
+}
+
+pub(super) fn recycled<I: IdAlloc>() {
+    if let Some(stats) = I::stats() {
+        stats.created(&stats.recycled)
+    }
+}
+
+pub(super) fn dropped<I: IdAlloc>() {
+    if let Some(stats) = I::stats() {
+        stats.live.fetch_sub(1, Relaxed);
+    }
+}
@@ -34,6 +34,8 @@
 
 use core::marker::PhantomData;
 
+use crate::slice::{BrandedSlice, Idx, IdxRange, Indices};
+
 /// Create a new scoped identifier with the provided name
 ///
 /// this identifier may be used until the end of the scope
@@ -137,6 +139,8 @@ impl Drop for AssertUniqueLifetime<'_> {
 }
 
 unsafe impl crate::Handle for ScopedHandle<'_> {}
+// This is safe because no other `Scoped` can have the same lifetime id
+unsafe impl crate::OneShotIdentifier for Scoped<'_> {}
 unsafe impl<'id> crate::Identifier for Scoped<'id> {
     type Handle = ScopedHandle<'id>;
 
@@ -146,3 +150,195 @@ unsafe impl<'id> crate::Identifier for Scoped<'id> {
     #[inline]
     fn owns(&self, _: &Self::Handle) -> bool { true }
 }
+
+forward_shared_identifier!(['id] Scoped<'id>);
+
+/// Two slices of the same length that are branded by a single [`Scoped`] identifier
+///
+/// Every [`Idx`] of the first slice is also in bounds of the second slice,
+/// so both can be indexed without any bounds checks
+///
+/// see [`same_len`] for details
+pub struct SameLen<'id, 'a, 'b, A, B> {
+    first: BrandedSlice<'a, Scoped<'id>, A>,
+    second: &'b [B],
+}
+
+/// Three slices of the same length that are branded by a single [`Scoped`] identifier
+///
+/// see [`same_len3`] for details
+pub struct SameLen3<'id, 'a, 'b, 'c, A, B, C> {
+    first: BrandedSlice<'a, Scoped<'id>, A>,
+    second: &'b [B],
+    third: &'c [C],
+}
+
+/// Brand two slices of the same length with the same [`Scoped`] identifier
+///
+/// This checks that the slices have the same length once, then any [`Idx`]
+/// from the [`SameLen`] can be used to index both of them without any bounds checks.
+/// If the slices have different lengths, then the callback isn't called and this returns `None`.
+///
+/// ```
+/// let a = [1, 2, 3];
+/// let b = [4, 5, 6];
+///
+/// let dot = pui::scoped::same_len(&a, &b, |ab| {
+///     ab.indices().map(|i| {
+///         let (a, b) = ab.get(i);
+///         a * b
+///     }).sum::<i32>()
+/// });
+/// assert_eq!(dot, Some(32));
+///
+/// assert_eq!(pui::scoped::same_len(&a, &b[1..], |_| ()), None);
+/// ```
+pub fn same_len<'a, 'b, A, B, R, F>(a: &'a [A], b: &'b [B], callback: F) -> Option<R>
+where
+    F: for<'id> FnOnce(SameLen<'id, 'a, 'b, A, B>) -> R,
+{
+    if a.len() != b.len() {
+        return None
+    }
+
+    Some(Scoped::with(move |ident| {
+        callback(SameLen {
+            first: BrandedSlice::new(a, ident),
+            second: b,
+        })
+    }))
+}
+
+/// Brand three slices of the same length with the same [`Scoped`] identifier
+///
+/// see [`same_len`] for details
+///
+/// ```
+/// let a = [1, 2, 3];
+/// let b = [4, 5, 6];
+/// let c = [7, 8, 9];
+///
+/// let sum = pui::scoped::same_len3(&a, &b, &c, |abc| {
+///     abc.indices().map(|i| {
+///         let (a, b, c) = abc.get(i);
+///         a + b + c
+///     }).collect::<Vec<_>>()
+/// });
+/// assert_eq!(sum, Some(vec![12, 15, 18]));
+/// ```
+pub fn same_len3<'a, 'b, 'c, A, B, C, R, F>(a: &'a [A], b: &'b [B], c: &'c [C], callback: F) -> Option<R>
+where
+    F: for<'id> FnOnce(SameLen3<'id, 'a, 'b, 'c, A, B, C>) -> R,
+{
+    if a.len() != b.len() || a.len() != c.len() {
+        return None
+    }
+
+    Some(Scoped::with(move |ident| {
+        callback(SameLen3 {
+            first: BrandedSlice::new(a, ident),
+            second: b,
+            third: c,
+        })
+    }))
+}
+
+impl<'id, 'a, 'b, A, B> SameLen<'id, 'a, 'b, A, B> {
+    /// The first slice, which brands all of the indices
+    #[inline]
+    pub fn first(&self) -> &BrandedSlice<'a, Scoped<'id>, A> { &self.first }
+
+    /// The second slice
+    #[inline]
+    pub fn second(&self) -> &'b [B] { self.second }
+
+    /// The length of the slices
+    #[inline]
+    pub fn len(&self) -> usize { self.first.len() }
+
+    /// Returns true if the slices are empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.first.is_empty() }
+
+    /// Check if `index` is in bounds, and if it is brand it
+    #[inline]
+    pub fn idx(&self, index: usize) -> Option<Idx<ScopedHandle<'id>>> { self.first.idx(index) }
+
+    /// An iterator over all indices of the slices
+    #[inline]
+    pub fn indices(&self) -> Indices<ScopedHandle<'id>> { self.first.indices() }
+
+    /// Get the elements of both slices at the given index
+    pub fn get(&self, idx: Idx<ScopedHandle<'id>>) -> (&'a A, &'b B) {
+        let index = idx.get();
+        let first = self.first.get(idx);
+
+        // This is safe because `idx` is branded by `'id`, so it was created by `first`,
+        // and is in bounds of `second`, which has the same length
+        (first, unsafe { self.second.get_unchecked(index) })
+    }
+
+    /// Get the elements of both slices in the given range
+    pub fn get_range(&self, range: IdxRange<ScopedHandle<'id>>) -> (&'a [A], &'b [B]) {
+        let bounds = range.get();
+        let first = self.first.get_range(range);
+
+        // This is safe for the same reason as in `get`
+        (first, unsafe { self.second.get_unchecked(bounds) })
+    }
+}
+
+impl<'id, 'a, 'b, 'c, A, B, C> SameLen3<'id, 'a, 'b, 'c, A, B, C> {
+    /// The first slice, which brands all of the indices
+    #[inline]
+    pub fn first(&self) -> &BrandedSlice<'a, Scoped<'id>, A> { &self.first }
+
+    /// The second slice
+    #[inline]
+    pub fn second(&self) -> &'b [B] { self.second }
+
+    /// The third slice
+    #[inline]
+    pub fn third(&self) -> &'c [C] { self.third }
+
+    /// The length of the slices
+    #[inline]
+    pub fn len(&self) -> usize { self.first.len() }
+
+    /// Returns true if the slices are empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.first.is_empty() }
+
+    /// Check if `index` is in bounds, and if it is brand it
+    #[inline]
+    pub fn idx(&self, index: usize) -> Option<Idx<ScopedHandle<'id>>> { self.first.idx(index) }
+
+    /// An iterator over all indices of the slices
+    #[inline]
+    pub fn indices(&self) -> Indices<ScopedHandle<'id>> { self.first.indices() }
+
+    /// Get the elements of all three slices at the given index
+    pub fn get(&self, idx: Idx<ScopedHandle<'id>>) -> (&'a A, &'b B, &'c C) {
+        let index = idx.get();
+        let first = self.first.get(idx);
+
+        // This is safe because `idx` is branded by `'id`, so it was created by `first`,
+        // and is in bounds of `second` and `third`, which have the same length
+        unsafe { (first, self.second.get_unchecked(index), self.third.get_unchecked(index)) }
+    }
+
+    /// Get the elements of all three slices in the given range
+    pub fn get_range(&self, range: IdxRange<ScopedHandle<'id>>) -> (&'a [A], &'b [B], &'c [C]) {
+        let bounds = range.get();
+        let first = self.first.get_range(range);
+
+        // This is safe for the same reason as in `get`
+        unsafe {
+            (
+                first,
+                self.second.get_unchecked(bounds.clone()),
+                self.third.get_unchecked(bounds),
+            )
+        }
+    }
+}
@@ -0,0 +1,355 @@
+//! Branded slices and indices
+//!
+//! A [`BrandedSlice`] takes ownership of a [`OneShotIdentifier`](crate::OneShotIdentifier),
+//! and ties it to a slice. Every [`Idx`] that it hands out is guaranteed to be in
+//! bounds for that slice, so indexing with an [`Idx`] doesn't need a bounds check.
+//! If the identifier's handle is [`Trivial`](crate::Trivial), like
+//! [`ScopedHandle`](crate::scoped::ScopedHandle), then the ownership check is a no-op too.
+//!
+//! ```
+//! use pui::{scoped::Scoped, slice::BrandedSlice};
+//!
+//! let values = [1, 2, 3, 4];
+//!
+//! Scoped::with(|ident| {
+//!     let slice = BrandedSlice::new(&values, ident);
+//!
+//!     let sum: i32 = slice.indices().map(|idx| slice[idx]).sum();
+//!     assert_eq!(sum, 10);
+//! })
+//! ```
+//!
+//! With the `alloc` feature, you can also use [`IdxSet`] and [`IdxMap`] to
+//! store dense sets of indices and secondary data without any bounds checks.
+//!
+//! [`BrandedSliceMut`] can be split into disjoint [`Chunk`]s, which can be
+//! branded again on other threads, see [`BrandedSliceMut::split_into`].
+
+use core::{iter::FusedIterator, ops};
+
+use crate::OneShotIdentifier;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod map;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod set;
+mod slice_mut;
+mod sorted;
+mod split;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use map::IdxMap;
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use set::IdxSet;
+pub use slice_mut::BrandedSliceMut;
+pub use sorted::SortedSlice;
+pub use split::{Chunk, SubSliceMut};
+
+/// A slice that is branded by a [`OneShotIdentifier`](crate::OneShotIdentifier)
+///
+/// The identifier must be one-shot, because an [`Idx`] must never be owned by
+/// the identifier of another slice, even after this slice is dropped.
+///
+/// see module docs for details
+pub struct BrandedSlice<'a, I, T> {
+    ident: I,
+    slice: &'a [T],
+}
+
+/// An index that is guaranteed to be in bounds of the [`BrandedSlice`]
+/// whose identifier owns `handle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Idx<H> {
+    index: usize,
+    handle: H,
+}
+
+/// A position between the elements of the [`BrandedSlice`] whose identifier owns `handle`
+///
+/// Unlike an [`Idx`], a position may be equal to the length of the slice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos<H> {
+    index: usize,
+    handle: H,
+}
+
+/// A range that is guaranteed to be in bounds of the [`BrandedSlice`]
+/// whose identifier owns `handle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdxRange<H> {
+    start: usize,
+    end: usize,
+    handle: H,
+}
+
+/// An iterator over all of the [`Idx`]s of a [`BrandedSlice`]
+///
+/// see [`BrandedSlice::indices`]
+#[derive(Debug, Clone)]
+pub struct Indices<H> {
+    range: ops::Range<usize>,
+    handle: H,
+}
+
+impl<H> Idx<H> {
+    /// Create a new index without checking if it is in bounds
+    ///
+    /// # Safety
+    ///
+    /// `index` must be in bounds of every collection whose identifier owns `handle`
+    #[inline]
+    pub const unsafe fn new_unchecked(index: usize, handle: H) -> Self { Self { index, handle } }
+
+    /// The underlying index
+    #[inline]
+    pub const fn get(&self) -> usize { self.index }
+
+    /// The handle that brands this index
+    #[inline]
+    pub const fn handle(&self) -> &H { &self.handle }
+}
+
+impl<H> Pos<H> {
+    /// The underlying position
+    #[inline]
+    pub const fn get(&self) -> usize { self.index }
+
+    /// The handle that brands this position
+    #[inline]
+    pub const fn handle(&self) -> &H { &self.handle }
+}
+
+impl<H> From<Idx<H>> for Pos<H> {
+    #[inline]
+    fn from(idx: Idx<H>) -> Self {
+        Self {
+            index: idx.index,
+            handle: idx.handle,
+        }
+    }
+}
+
+impl<H> IdxRange<H> {
+    /// The underlying range
+    #[inline]
+    pub fn get(&self) -> ops::Range<usize> { self.start..self.end }
+
+    /// The number of elements in the range
+    #[inline]
+    pub const fn len(&self) -> usize { self.end - self.start }
+
+    /// Returns true if the range is empty
+    #[inline]
+    pub const fn is_empty(&self) -> bool { self.start == self.end }
+
+    /// The handle that brands this range
+    #[inline]
+    pub const fn handle(&self) -> &H { &self.handle }
+}
+
+impl<H: Clone> IdxRange<H> {
+    /// The position where this range starts
+    #[inline]
+    pub fn start(&self) -> Pos<H> {
+        Pos {
+            index: self.start,
+            handle: self.handle.clone(),
+        }
+    }
+
+    /// The position where this range ends
+    #[inline]
+    pub fn end(&self) -> Pos<H> {
+        Pos {
+            index: self.end,
+            handle: self.handle.clone(),
+        }
+    }
+
+    /// An iterator over all indices in this range
+    #[inline]
+    pub fn indices(&self) -> Indices<H> {
+        Indices {
+            range: self.start..self.end,
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<'a, I: OneShotIdentifier, T> BrandedSlice<'a, I, T> {
+    /// Brand the given slice with the identifier
+    #[inline]
+    pub fn new(slice: &'a [T], ident: I) -> Self { Self { ident, slice } }
+
+    /// The identifier that brands this slice
+    #[inline]
+    pub fn identifier(&self) -> &I { &self.ident }
+
+    /// The underlying slice
+    #[inline]
+    pub fn as_slice(&self) -> &'a [T] { self.slice }
+
+    /// The length of the slice
+    #[inline]
+    pub fn len(&self) -> usize { self.slice.len() }
+
+    /// Returns true if the slice is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.slice.is_empty() }
+
+    /// Check if the given index was created by this slice
+    #[inline]
+    pub fn owns(&self, idx: &Idx<I::Handle>) -> bool { self.ident.owns(&idx.handle) }
+
+    /// Check if `index` is in bounds, and if it is brand it
+    pub fn idx(&self, index: usize) -> Option<Idx<I::Handle>> {
+        if index < self.slice.len() {
+            Some(Idx {
+                index,
+                handle: self.ident.handle(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Check if `index` is a position in this slice, and if it is brand it
+    ///
+    /// Every index up to and including the length of the slice is a position
+    pub fn pos(&self, index: usize) -> Option<Pos<I::Handle>> {
+        if index <= self.slice.len() {
+            Some(Pos {
+                index,
+                handle: self.ident.handle(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Create a range between two positions of this slice
+    ///
+    /// # Panic
+    ///
+    /// If either position is not owned by this slice, or if `start > end`
+    pub fn span(&self, start: Pos<I::Handle>, end: Pos<I::Handle>) -> IdxRange<I::Handle> {
+        assert!(
+            self.ident.owns(&start.handle) && self.ident.owns(&end.handle),
+            "tried to use a position that is not owned by this slice"
+        );
+
+        assert!(
+            start.index <= end.index,
+            "slice index starts at {} but ends at {}",
+            start.index,
+            end.index
+        );
+
+        IdxRange {
+            start: start.index,
+            end: end.index,
+            handle: start.handle,
+        }
+    }
+
+    /// An iterator over all indices of this slice
+    #[inline]
+    pub fn indices(&self) -> Indices<I::Handle> {
+        Indices {
+            range: 0..self.slice.len(),
+            handle: self.ident.handle(),
+        }
+    }
+
+    /// Get the element at the given index
+    ///
+    /// # Panic
+    ///
+    /// If the index is not owned by this slice
+    pub fn get(&self, idx: Idx<I::Handle>) -> &'a T {
+        assert!(
+            self.ident.owns(&idx.handle),
+            "tried to use an index that is not owned by this slice"
+        );
+
+        // This is safe because `ident` owns the `idx`, and it is one-shot, so `idx`
+        // was created by this slice, which means that it is in bounds
+        unsafe { self.slice.get_unchecked(idx.index) }
+    }
+
+    /// Get the elements in the given range
+    ///
+    /// # Panic
+    ///
+    /// If the range is not owned by this slice
+    pub fn get_range(&self, range: IdxRange<I::Handle>) -> &'a [T] {
+        assert!(
+            self.ident.owns(&range.handle),
+            "tried to use a range that is not owned by this slice"
+        );
+
+        // This is safe because `ident` owns the `range`, and it is one-shot, so `range`
+        // was created by this slice, which means that it is in bounds and `start <= end`
+        unsafe { self.slice.get_unchecked(range.start..range.end) }
+    }
+}
+
+impl<I: OneShotIdentifier, T> ops::Index<Idx<I::Handle>> for BrandedSlice<'_, I, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, idx: Idx<I::Handle>) -> &Self::Output { self.get(idx) }
+}
+
+impl<I: OneShotIdentifier, T> ops::Index<IdxRange<I::Handle>> for BrandedSlice<'_, I, T> {
+    type Output = [T];
+
+    #[inline]
+    fn index(&self, range: IdxRange<I::Handle>) -> &Self::Output { self.get_range(range) }
+}
+
+impl<H: Clone> Iterator for Indices<H> {
+    type Item = Idx<H>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.range.next()?;
+        Some(Idx {
+            index,
+            handle: self.handle.clone(),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) { self.range.size_hint() }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth(n)?;
+        Some(Idx {
+            index,
+            handle: self.handle.clone(),
+        })
+    }
+}
+
+impl<H: Clone> DoubleEndedIterator for Indices<H> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.range.next_back()?;
+        Some(Idx {
+            index,
+            handle: self.handle.clone(),
+        })
+    }
+}
+
+impl<H: Clone> ExactSizeIterator for Indices<H> {}
+impl<H: Clone> FusedIterator for Indices<H> {}
+
+use core::fmt;
+impl<I, T: fmt::Debug> fmt::Debug for BrandedSlice<'_, I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.slice.fmt(f) }
+}
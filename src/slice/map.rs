@@ -0,0 +1,172 @@
+use super::{BrandedSlice, Idx};
+use crate::OneShotIdentifier;
+
+use core::{fmt, ops};
+use std::{boxed::Box, vec::Vec};
+
+/// A dense map from the [`Idx`]s of a [`BrandedSlice`] to values
+///
+/// This is secondary storage for a slice, it has a slot for every index in
+/// the slice it was created from, so accessing it doesn't need any bounds checks.
+///
+/// ```
+/// use pui::{scoped::Scoped, slice::{BrandedSlice, IdxMap}};
+///
+/// let names = ["alice", "bob", "carol"];
+///
+/// Scoped::with(|ident| {
+///     let slice = BrandedSlice::new(&names, ident);
+///     let mut lengths = IdxMap::new(&slice);
+///
+///     for idx in slice.indices() {
+///         lengths.insert(idx, slice[idx].len());
+///     }
+///
+///     let bob = slice.idx(1).unwrap();
+///     assert_eq!(lengths[bob], 3);
+///     assert_eq!(lengths.remove(bob), Some(3));
+///     assert_eq!(lengths.get(bob), None);
+/// })
+/// ```
+pub struct IdxMap<'s, I, V> {
+    ident: &'s I,
+    len: usize,
+    values: Box<[Option<V>]>,
+}
+
+impl<'s, I: OneShotIdentifier, V> IdxMap<'s, I, V> {
+    /// Create a new empty map that can hold any index of the given slice
+    pub fn new<T>(slice: &'s BrandedSlice<'_, I, T>) -> Self {
+        let mut values = Vec::new();
+        values.resize_with(slice.len(), || None);
+
+        Self {
+            ident: slice.identifier(),
+            len: 0,
+            values: values.into_boxed_slice(),
+        }
+    }
+
+    /// The number of indices that this map can hold,
+    /// this is the length of the slice it was created from
+    #[inline]
+    pub fn capacity(&self) -> usize { self.values.len() }
+
+    /// The number of values in the map
+    #[inline]
+    pub fn len(&self) -> usize { self.len }
+
+    /// Returns true if there are no values in the map
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Remove all values from the map
+    pub fn clear(&mut self) {
+        self.values.iter_mut().for_each(|value| *value = None);
+        self.len = 0;
+    }
+
+    fn slot(&self, idx: &Idx<I::Handle>) -> &Option<V> {
+        assert!(
+            self.ident.owns(&idx.handle),
+            "tried to use an index that is not owned by this map's slice"
+        );
+
+        // This is safe because `idx` is owned by the one-shot identifier of the slice
+        // that this map was created from, so it was created by that slice, and is
+        // less than `capacity`
+        unsafe { self.values.get_unchecked(idx.index) }
+    }
+
+    fn slot_mut(&mut self, idx: &Idx<I::Handle>) -> &mut Option<V> {
+        assert!(
+            self.ident.owns(&idx.handle),
+            "tried to use an index that is not owned by this map's slice"
+        );
+
+        // This is safe because `idx` is owned by the one-shot identifier of the slice
+        // that this map was created from, so it was created by that slice, and is
+        // less than `capacity`
+        unsafe { self.values.get_unchecked_mut(idx.index) }
+    }
+
+    /// Insert a value at the given index, and return the old value
+    pub fn insert(&mut self, idx: Idx<I::Handle>, value: V) -> Option<V> {
+        let old = self.slot_mut(&idx).replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Remove the value at the given index
+    pub fn remove(&mut self, idx: Idx<I::Handle>) -> Option<V> {
+        let old = self.slot_mut(&idx).take();
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    /// Check if there is a value at the given index
+    pub fn contains_key(&self, idx: Idx<I::Handle>) -> bool { self.slot(&idx).is_some() }
+
+    /// Get a reference to the value at the given index
+    pub fn get(&self, idx: Idx<I::Handle>) -> Option<&V> { self.slot(&idx).as_ref() }
+
+    /// Get a mutable reference to the value at the given index
+    pub fn get_mut(&mut self, idx: Idx<I::Handle>) -> Option<&mut V> { self.slot_mut(&idx).as_mut() }
+
+    /// An iterator over all indices and values in the map, in ascending order of index
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (Idx<I::Handle>, &V)> {
+        self.values.iter().enumerate().filter_map(move |(index, value)| {
+            let idx = Idx {
+                index,
+                handle: self.ident.handle(),
+            };
+            Some((idx, value.as_ref()?))
+        })
+    }
+
+    /// An iterator over all values in the map, in ascending order of index
+    pub fn values(&self) -> impl '_ + Iterator<Item = &V> { self.values.iter().flatten() }
+
+    /// An iterator over mutable references to all values in the map, in ascending order of index
+    pub fn values_mut(&mut self) -> impl '_ + Iterator<Item = &mut V> { self.values.iter_mut().flatten() }
+}
+
+impl<I: OneShotIdentifier, V> ops::Index<Idx<I::Handle>> for IdxMap<'_, I, V> {
+    type Output = V;
+
+    fn index(&self, idx: Idx<I::Handle>) -> &Self::Output {
+        let index = idx.index;
+        self.get(idx)
+            .unwrap_or_else(|| panic!("there is no value at index {} in the map", index))
+    }
+}
+
+impl<I: OneShotIdentifier, V> ops::IndexMut<Idx<I::Handle>> for IdxMap<'_, I, V> {
+    fn index_mut(&mut self, idx: Idx<I::Handle>) -> &mut Self::Output {
+        let index = idx.index;
+        self.get_mut(idx)
+            .unwrap_or_else(|| panic!("there is no value at index {} in the map", index))
+    }
+}
+
+impl<I, V: Clone> Clone for IdxMap<'_, I, V> {
+    fn clone(&self) -> Self {
+        Self {
+            ident: self.ident,
+            len: self.len,
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<I: OneShotIdentifier, V: fmt::Debug> fmt::Debug for IdxMap<'_, I, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(idx, value)| (idx.index, value)))
+            .finish()
+    }
+}
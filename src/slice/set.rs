@@ -0,0 +1,133 @@
+use super::{BrandedSlice, Idx};
+use crate::OneShotIdentifier;
+
+use core::fmt;
+use std::{boxed::Box, vec};
+
+const BITS: usize = usize::BITS as usize;
+
+/// A dense bit set of [`Idx`]s of a [`BrandedSlice`]
+///
+/// The set is sized to the slice it was created from, so inserting and
+/// checking for indices doesn't need any bounds checks.
+///
+/// ```
+/// use pui::{scoped::Scoped, slice::{BrandedSlice, IdxSet}};
+///
+/// let values = [3, 1, 4, 1, 5, 9, 2, 6];
+///
+/// Scoped::with(|ident| {
+///     let slice = BrandedSlice::new(&values, ident);
+///     let mut odd = IdxSet::new(&slice);
+///
+///     for idx in slice.indices() {
+///         if slice[idx] % 2 == 1 {
+///             odd.insert(idx);
+///         }
+///     }
+///
+///     assert_eq!(odd.len(), 5);
+///     assert!(odd.contains(slice.idx(5).unwrap()));
+///     assert!(!odd.contains(slice.idx(6).unwrap()));
+/// })
+/// ```
+pub struct IdxSet<'s, I> {
+    ident: &'s I,
+    capacity: usize,
+    words: Box<[usize]>,
+}
+
+impl<'s, I: OneShotIdentifier> IdxSet<'s, I> {
+    /// Create a new empty set that can hold any index of the given slice
+    pub fn new<T>(slice: &'s BrandedSlice<'_, I, T>) -> Self {
+        let capacity = slice.len();
+
+        Self {
+            ident: slice.identifier(),
+            capacity,
+            words: vec![0; capacity.div_ceil(BITS)].into_boxed_slice(),
+        }
+    }
+
+    /// The number of indices that this set can hold,
+    /// this is the length of the slice it was created from
+    #[inline]
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    /// The number of indices in the set
+    pub fn len(&self) -> usize { self.words.iter().map(|word| word.count_ones() as usize).sum() }
+
+    /// Returns true if there are no indices in the set
+    pub fn is_empty(&self) -> bool { self.words.iter().all(|&word| word == 0) }
+
+    /// Remove all indices from the set
+    pub fn clear(&mut self) { self.words.iter_mut().for_each(|word| *word = 0); }
+
+    fn locate(&self, idx: &Idx<I::Handle>) -> (usize, usize) {
+        assert!(
+            self.ident.owns(&idx.handle),
+            "tried to use an index that is not owned by this set's slice"
+        );
+
+        (idx.index / BITS, 1 << (idx.index % BITS))
+    }
+
+    /// Add an index to the set, returns true if the index wasn't in the set
+    pub fn insert(&mut self, idx: Idx<I::Handle>) -> bool {
+        let (word, mask) = self.locate(&idx);
+
+        // This is safe because `idx` is owned by the one-shot identifier of the slice
+        // that this set was created from, so it is less than `capacity`
+        let word = unsafe { self.words.get_unchecked_mut(word) };
+        let inserted = *word & mask == 0;
+        *word |= mask;
+        inserted
+    }
+
+    /// Remove an index from the set, returns true if the index was in the set
+    pub fn remove(&mut self, idx: Idx<I::Handle>) -> bool {
+        let (word, mask) = self.locate(&idx);
+
+        // This is safe because `idx` is owned by the one-shot identifier of the slice
+        // that this set was created from, so it is less than `capacity`
+        let word = unsafe { self.words.get_unchecked_mut(word) };
+        let removed = *word & mask != 0;
+        *word &= !mask;
+        removed
+    }
+
+    /// Check if the index is in the set
+    pub fn contains(&self, idx: Idx<I::Handle>) -> bool {
+        let (word, mask) = self.locate(&idx);
+
+        // This is safe because `idx` is owned by the one-shot identifier of the slice
+        // that this set was created from, so it is less than `capacity`
+        unsafe { self.words.get_unchecked(word) & mask != 0 }
+    }
+
+    /// An iterator over all indices in the set, in ascending order
+    pub fn iter(&self) -> impl '_ + Iterator<Item = Idx<I::Handle>> {
+        self.words.iter().enumerate().flat_map(move |(i, &word)| {
+            (0..BITS).filter(move |bit| word & (1 << bit) != 0).map(move |bit| Idx {
+                index: i * BITS + bit,
+                handle: self.ident.handle(),
+            })
+        })
+    }
+}
+
+impl<I> Clone for IdxSet<'_, I> {
+    fn clone(&self) -> Self {
+        Self {
+            ident: self.ident,
+            capacity: self.capacity,
+            words: self.words.clone(),
+        }
+    }
+}
+
+impl<I: OneShotIdentifier> fmt::Debug for IdxSet<'_, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter().map(|idx| idx.index)).finish()
+    }
+}
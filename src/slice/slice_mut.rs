@@ -0,0 +1,136 @@
+use super::{Idx, Indices};
+use crate::OneShotIdentifier;
+
+use core::{fmt, ops};
+
+/// A mutable slice that is branded by a [`OneShotIdentifier`](crate::OneShotIdentifier)
+///
+/// This works just like [`BrandedSlice`](super::BrandedSlice), but it also allows you to
+/// write through the branded indices. You can also split it into disjoint [`Chunk`](super::Chunk)s,
+/// which can each be branded again and processed in parallel.
+///
+/// ```
+/// use pui::{scoped::Scoped, slice::BrandedSliceMut};
+///
+/// let mut values = [1, 2, 3, 4];
+///
+/// Scoped::with(|ident| {
+///     let mut slice = BrandedSliceMut::new(&mut values, ident);
+///
+///     for idx in slice.indices() {
+///         slice[idx] *= 10;
+///     }
+/// });
+///
+/// assert_eq!(values, [10, 20, 30, 40]);
+/// ```
+pub struct BrandedSliceMut<'a, I, T> {
+    pub(super) ident: I,
+    pub(super) slice: &'a mut [T],
+}
+
+impl<'a, I: OneShotIdentifier, T> BrandedSliceMut<'a, I, T> {
+    /// Brand the given slice with the identifier
+    #[inline]
+    pub fn new(slice: &'a mut [T], ident: I) -> Self { Self { ident, slice } }
+
+    /// The identifier that brands this slice
+    #[inline]
+    pub fn identifier(&self) -> &I { &self.ident }
+
+    /// The underlying slice
+    #[inline]
+    pub fn as_slice(&self) -> &[T] { self.slice }
+
+    /// The underlying slice
+    ///
+    /// This can't change the length of the slice, so all indices stay valid
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] { self.slice }
+
+    /// Get the underlying slice
+    #[inline]
+    pub fn into_slice(self) -> &'a mut [T] { self.slice }
+
+    /// The length of the slice
+    #[inline]
+    pub fn len(&self) -> usize { self.slice.len() }
+
+    /// Returns true if the slice is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.slice.is_empty() }
+
+    /// Check if the given index was created by this slice
+    #[inline]
+    pub fn owns(&self, idx: &Idx<I::Handle>) -> bool { self.ident.owns(&idx.handle) }
+
+    /// Check if `index` is in bounds, and if it is brand it
+    pub fn idx(&self, index: usize) -> Option<Idx<I::Handle>> {
+        if index < self.slice.len() {
+            Some(Idx {
+                index,
+                handle: self.ident.handle(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// An iterator over all indices of this slice
+    #[inline]
+    pub fn indices(&self) -> Indices<I::Handle> {
+        Indices {
+            range: 0..self.slice.len(),
+            handle: self.ident.handle(),
+        }
+    }
+
+    fn index_of(&self, idx: &Idx<I::Handle>) -> usize {
+        assert!(
+            self.ident.owns(&idx.handle),
+            "tried to use an index that is not owned by this slice"
+        );
+
+        idx.index
+    }
+
+    /// Get the element at the given index
+    ///
+    /// # Panic
+    ///
+    /// If the index is not owned by this slice
+    pub fn get(&self, idx: Idx<I::Handle>) -> &T {
+        let index = self.index_of(&idx);
+        // This is safe because `ident` owns the `idx`, and it is one-shot, so `idx`
+        // was created by this slice, which means that it is in bounds
+        unsafe { self.slice.get_unchecked(index) }
+    }
+
+    /// Get the element at the given index
+    ///
+    /// # Panic
+    ///
+    /// If the index is not owned by this slice
+    pub fn get_mut(&mut self, idx: Idx<I::Handle>) -> &mut T {
+        let index = self.index_of(&idx);
+        // This is safe because `ident` owns the `idx`, and it is one-shot, so `idx`
+        // was created by this slice, which means that it is in bounds
+        unsafe { self.slice.get_unchecked_mut(index) }
+    }
+}
+
+impl<I: OneShotIdentifier, T> ops::Index<Idx<I::Handle>> for BrandedSliceMut<'_, I, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, idx: Idx<I::Handle>) -> &Self::Output { self.get(idx) }
+}
+
+impl<I: OneShotIdentifier, T> ops::IndexMut<Idx<I::Handle>> for BrandedSliceMut<'_, I, T> {
+    #[inline]
+    fn index_mut(&mut self, idx: Idx<I::Handle>) -> &mut Self::Output { self.get_mut(idx) }
+}
+
+impl<I, T: fmt::Debug> fmt::Debug for BrandedSliceMut<'_, I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.slice.fmt(f) }
+}
@@ -0,0 +1,207 @@
+use super::{BrandedSlice, Idx, IdxRange, Pos};
+use crate::OneShotIdentifier;
+
+use core::{
+    cmp::Ordering,
+    fmt,
+    ops::{self, Bound, RangeBounds},
+};
+#[cfg(any(feature = "std", feature = "alloc"))]
+use std::vec::Vec;
+
+/// A [`BrandedSlice`] that is known to be sorted in ascending order
+///
+/// The sortedness is checked once when the `SortedSlice` is created, after that
+/// searching it hands out [`Idx`]s, [`Pos`]s, and [`IdxRange`]s that can be used to
+/// index into the slice without any bounds checks.
+///
+/// Note: sortedness is only a logical invariant, if `T`'s `Ord` impl is inconsistent
+/// the search results may be wrong, but they will always be in bounds.
+///
+/// ```
+/// use pui::{scoped::Scoped, slice::SortedSlice};
+///
+/// let values = [1, 3, 3, 5, 8, 13];
+///
+/// Scoped::with(|ident| {
+///     let sorted = SortedSlice::new(&values, ident).ok().unwrap();
+///
+///     let five = sorted.binary_search(&5).unwrap();
+///     assert_eq!(sorted[five], 5);
+///
+///     let small = sorted.range(2..=5);
+///     assert_eq!(sorted[small], [3, 3, 5]);
+///
+///     let insert_at = sorted.binary_search(&4).unwrap_err();
+///     assert_eq!(insert_at.get(), 3);
+/// })
+/// ```
+pub struct SortedSlice<'a, I, T> {
+    slice: BrandedSlice<'a, I, T>,
+}
+
+impl<'a, I: OneShotIdentifier, T: Ord> SortedSlice<'a, I, T> {
+    /// Brand the given slice with the identifier if it is sorted
+    ///
+    /// If it isn't sorted, the branded slice is returned in the error
+    pub fn new(slice: &'a [T], ident: I) -> Result<Self, BrandedSlice<'a, I, T>> {
+        Self::from_branded(BrandedSlice::new(slice, ident))
+    }
+
+    /// Check if the branded slice is sorted
+    ///
+    /// If it isn't sorted, the branded slice is returned in the error
+    pub fn from_branded(slice: BrandedSlice<'a, I, T>) -> Result<Self, BrandedSlice<'a, I, T>> {
+        if slice.as_slice().windows(2).all(|pair| pair[0] <= pair[1]) {
+            Ok(Self { slice })
+        } else {
+            Err(slice)
+        }
+    }
+
+    /// Sort the given slice, then brand it with the identifier
+    ///
+    /// This uses an unstable sort, so that it is available without `alloc`
+    pub fn sort(slice: &'a mut [T], ident: I) -> Self {
+        slice.sort_unstable();
+        Self {
+            slice: BrandedSlice::new(slice, ident),
+        }
+    }
+
+    /// Search for `value`
+    ///
+    /// If the value is found, returns the index of a matching element. If there are multiple
+    /// matches, then any one of them may be returned. If the value is not found, returns the
+    /// position where it could be inserted while keeping the slice sorted.
+    pub fn binary_search(&self, value: &T) -> Result<Idx<I::Handle>, Pos<I::Handle>> {
+        self.binary_search_by(|probe| probe.cmp(value))
+    }
+
+    /// Find the position that starts the range of elements contained in `range`,
+    /// and the position that ends it
+    ///
+    /// If the range is empty or contains no elements of the slice, an empty range is returned
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> IdxRange<I::Handle> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.partition_point(|x| x < start),
+            Bound::Excluded(start) => self.partition_point(|x| x <= start),
+            Bound::Unbounded => self.slice.pos(0).unwrap(),
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.partition_point(|x| x <= end),
+            Bound::Excluded(end) => self.partition_point(|x| x < end),
+            Bound::Unbounded => self.slice.pos(self.slice.len()).unwrap(),
+        };
+
+        IdxRange {
+            start: start.index,
+            // an empty range like `5..3` would otherwise end before it starts
+            end: end.index.max(start.index),
+            handle: start.handle,
+        }
+    }
+
+    /// Merge this slice with another sorted slice into a new sorted `Vec`
+    ///
+    /// The merge is stable, equal elements from `self` come before those from `other`
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(doc, doc(cfg(any(feature = "std", feature = "alloc"))))]
+    pub fn merge<J: OneShotIdentifier>(&self, other: &SortedSlice<'_, J, T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let (mut left, mut right) = (self.as_slice(), other.as_slice());
+        let mut merged = Vec::with_capacity(left.len() + right.len());
+
+        while let (Some((a, rest_left)), Some((b, rest_right))) = (left.split_first(), right.split_first()) {
+            if b < a {
+                merged.push(b.clone());
+                right = rest_right;
+            } else {
+                merged.push(a.clone());
+                left = rest_left;
+            }
+        }
+
+        merged.extend_from_slice(left);
+        merged.extend_from_slice(right);
+        merged
+    }
+}
+
+impl<'a, I: OneShotIdentifier, T> SortedSlice<'a, I, T> {
+    /// Brand the given slice with the identifier without checking if it is sorted
+    ///
+    /// This is not `unsafe`, because sortedness is only a logical invariant,
+    /// but searches on an unsorted slice will return meaningless results
+    #[inline]
+    pub fn new_assume_sorted(slice: &'a [T], ident: I) -> Self {
+        Self {
+            slice: BrandedSlice::new(slice, ident),
+        }
+    }
+
+    /// The underlying branded slice
+    #[inline]
+    pub fn as_branded(&self) -> &BrandedSlice<'a, I, T> { &self.slice }
+
+    /// Get the underlying branded slice
+    #[inline]
+    pub fn into_branded(self) -> BrandedSlice<'a, I, T> { self.slice }
+
+    /// Search with a comparator function, see [`SortedSlice::binary_search`] for details
+    ///
+    /// The comparator must be consistent with the order of the slice
+    pub fn binary_search_by<F: FnMut(&T) -> Ordering>(&self, f: F) -> Result<Idx<I::Handle>, Pos<I::Handle>> {
+        let handle = self.slice.identifier().handle();
+        let len = self.slice.len();
+        // the result is unspecified if `f` is inconsistent, so it must be checked before it is
+        // branded, because other methods use an `Idx` or `Pos` as proof that it is in bounds
+        match self.slice.as_slice().binary_search_by(f) {
+            Ok(index) => {
+                assert!(index < len, "`binary_search_by` returned an out of bounds index");
+                Ok(Idx { index, handle })
+            }
+            Err(index) => Err(Pos {
+                index: index.min(len),
+                handle,
+            }),
+        }
+    }
+
+    /// Search for a key, see [`SortedSlice::binary_search`] for details
+    ///
+    /// The slice must be sorted by the key
+    pub fn binary_search_by_key<B: Ord, F: FnMut(&T) -> B>(
+        &self,
+        key: &B,
+        mut f: F,
+    ) -> Result<Idx<I::Handle>, Pos<I::Handle>> {
+        self.binary_search_by(|probe| f(probe).cmp(key))
+    }
+
+    /// Find the first position where the predicate is false
+    ///
+    /// The slice must be partitioned by the predicate, i.e. all elements
+    /// for which the predicate is true must come before all the others
+    pub fn partition_point<F: FnMut(&T) -> bool>(&self, f: F) -> Pos<I::Handle> {
+        // the result is unspecified if the slice isn't partitioned, see `binary_search_by`
+        Pos {
+            index: self.slice.as_slice().partition_point(f).min(self.slice.len()),
+            handle: self.slice.identifier().handle(),
+        }
+    }
+}
+
+impl<'a, I, T> ops::Deref for SortedSlice<'a, I, T> {
+    type Target = BrandedSlice<'a, I, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.slice }
+}
+
+impl<I, T: fmt::Debug> fmt::Debug for SortedSlice<'_, I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.slice.fmt(f) }
+}
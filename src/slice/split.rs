@@ -0,0 +1,198 @@
+use super::{BrandedSliceMut, Idx};
+use crate::{
+    scoped::{Scoped, ScopedHandle},
+    OneShotIdentifier,
+};
+
+use core::{fmt, mem, ops};
+
+/// A disjoint part of a [`BrandedSliceMut`]
+///
+/// Chunks are not branded themselves, because each chunk has a different length.
+/// Instead [`Chunk::with`] brands the chunk with a fresh [`Scoped`] identifier, and
+/// remembers where the chunk is in the parent slice, so indices can be translated
+/// back to the parent slice.
+///
+/// Chunks are `Send`, so they can be moved into threads, see [`BrandedSliceMut::split_into`]
+pub struct Chunk<'a, H, T> {
+    offset: usize,
+    parent: H,
+    slice: &'a mut [T],
+}
+
+/// A [`Chunk`] that is branded by a fresh [`Scoped`] identifier
+///
+/// This derefs to a [`BrandedSliceMut`], so you can use it just like one.
+/// Use [`SubSliceMut::to_parent`] to translate its indices back to the parent slice.
+pub struct SubSliceMut<'a, 'id, H, T> {
+    offset: usize,
+    parent: H,
+    slice: BrandedSliceMut<'a, Scoped<'id>, T>,
+}
+
+impl<'a, I: OneShotIdentifier, T> BrandedSliceMut<'a, I, T> {
+    /// Split this slice into `N` disjoint chunks of nearly equal size
+    ///
+    /// If the length of the slice isn't divisible by `N`, the first chunks
+    /// will be one element longer than the last chunks.
+    ///
+    /// ```
+    /// use pui::{scoped::Scoped, slice::BrandedSliceMut};
+    ///
+    /// let mut values: Vec<u32> = (0..100).collect();
+    ///
+    /// Scoped::with(|ident| {
+    ///     let mut slice = BrandedSliceMut::new(&mut values, ident);
+    ///
+    ///     let maxima = std::thread::scope(|scope| {
+    ///         let mut threads = Vec::new();
+    ///
+    ///         for chunk in slice.split_into::<4>() {
+    ///             threads.push(scope.spawn(move || {
+    ///                 chunk.with(|mut sub| {
+    ///                     let max = sub.indices().max_by_key(|&idx| sub[idx]).unwrap();
+    ///                     sub[max] += 1000;
+    ///                     sub.to_parent(max)
+    ///                 })
+    ///             }));
+    ///         }
+    ///
+    ///         threads.into_iter().map(|thread| thread.join().unwrap()).collect::<Vec<_>>()
+    ///     });
+    ///
+    ///     let maxima: Vec<_> = maxima.into_iter().map(|idx| slice[idx]).collect();
+    ///     assert_eq!(maxima, [1024, 1049, 1074, 1099]);
+    /// })
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// If `N == 0`
+    pub fn split_into<const N: usize>(&mut self) -> [Chunk<'_, I::Handle, T>; N] {
+        assert!(N != 0, "tried to split a slice into 0 chunks");
+
+        let (size, extra) = (self.slice.len() / N, self.slice.len() % N);
+        let handle = self.ident.handle();
+        let mut rest: &mut [T] = self.slice;
+        let mut offset = 0;
+
+        core::array::from_fn(|i| {
+            let len = size + usize::from(i < extra);
+            let (slice, tail) = mem::take(&mut rest).split_at_mut(len);
+            rest = tail;
+
+            let chunk = Chunk {
+                offset,
+                parent: handle.clone(),
+                slice,
+            };
+            offset += len;
+            chunk
+        })
+    }
+
+    /// An iterator over disjoint chunks of this slice with `size` elements each,
+    /// the last chunk may be shorter
+    ///
+    /// # Panic
+    ///
+    /// If `size == 0`
+    pub fn chunks(&mut self, size: usize) -> impl '_ + Iterator<Item = Chunk<'_, I::Handle, T>> {
+        let handle = self.ident.handle();
+        self.slice.chunks_mut(size).enumerate().map(move |(i, slice)| Chunk {
+            offset: i * size,
+            parent: handle.clone(),
+            slice,
+        })
+    }
+}
+
+impl<'a, H, T> Chunk<'a, H, T> {
+    /// The index in the parent slice where this chunk starts
+    #[inline]
+    pub fn offset(&self) -> usize { self.offset }
+
+    /// The handle of the parent slice
+    #[inline]
+    pub fn parent_handle(&self) -> &H { &self.parent }
+
+    /// The length of the chunk
+    #[inline]
+    pub fn len(&self) -> usize { self.slice.len() }
+
+    /// Returns true if the chunk is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.slice.is_empty() }
+
+    /// The underlying slice
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] { self.slice }
+
+    /// Brand this chunk with a fresh [`Scoped`] identifier
+    pub fn with<R, F>(self, f: F) -> R
+    where
+        F: for<'id> FnOnce(SubSliceMut<'a, 'id, H, T>) -> R,
+    {
+        let Self { offset, parent, slice } = self;
+        Scoped::with(move |ident| {
+            f(SubSliceMut {
+                offset,
+                parent,
+                slice: BrandedSliceMut::new(slice, ident),
+            })
+        })
+    }
+}
+
+impl<'id, H: Clone, T> SubSliceMut<'_, 'id, H, T> {
+    /// The index in the parent slice where this sub-slice starts
+    #[inline]
+    pub fn offset(&self) -> usize { self.offset }
+
+    /// The handle of the parent slice
+    #[inline]
+    pub fn parent_handle(&self) -> &H { &self.parent }
+
+    /// Translate an index of this sub-slice to the corresponding index of the parent slice
+    pub fn to_parent(&self, idx: Idx<ScopedHandle<'id>>) -> Idx<H> {
+        // `Scoped<'id>` owns every `ScopedHandle<'id>`, and there is only one `Scoped<'id>`,
+        // so `idx` must be in bounds of this sub-slice. The sub-slice is in bounds of the
+        // parent from `offset`, so the translated index is in bounds of the parent.
+        Idx {
+            index: self.offset + idx.index,
+            handle: self.parent.clone(),
+        }
+    }
+}
+
+impl<'a, 'id, H, T> ops::Deref for SubSliceMut<'a, 'id, H, T> {
+    type Target = BrandedSliceMut<'a, Scoped<'id>, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.slice }
+}
+
+impl<'a, 'id, H, T> ops::DerefMut for SubSliceMut<'a, 'id, H, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.slice }
+}
+
+impl<H: fmt::Debug, T: fmt::Debug> fmt::Debug for Chunk<'_, H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chunk")
+            .field("offset", &self.offset)
+            .field("parent", &self.parent)
+            .field("slice", &self.slice)
+            .finish()
+    }
+}
+
+impl<H: fmt::Debug, T: fmt::Debug> fmt::Debug for SubSliceMut<'_, '_, H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubSliceMut")
+            .field("offset", &self.offset)
+            .field("parent", &self.parent)
+            .field("slice", &self.slice)
+            .finish()
+    }
+}
@@ -0,0 +1,119 @@
+// these lints fire inside of the expansions of `make_global_id_alloc_tl` and `make_global_pool`
+#![allow(const_item_mutation, missing_docs)]
+
+use core::cell::RefCell;
+use std::thread_local;
+
+use crate::{
+    runtime::{Runtime, RuntimeHandle},
+    Identifier, OwnershipError,
+};
+
+// `ThreadOwnerIdAlloc` and `ThreadOwnerPool` must be `pub` because they appear in `ThreadOwnerHandle`,
+// but they are implementation details, so they aren't exported from the crate
+crate::make_global_id_alloc_tl! {
+    /// The id allocator for [`ThreadOwner`]s
+    pub type ThreadOwnerIdAlloc(
+        /// The id of a [`ThreadOwner`]
+        ThreadOwnerId
+    ) = core::num::NonZeroU64;
+}
+
+crate::make_global_pool! {
+    /// The thread local pool that [`ThreadOwner`] ids are returned to
+    pub thread_local one ThreadOwnerPool(ThreadOwnerId);
+}
+
+/// The identifier that is lazily created for each thread, see [`with_thread_owner`]
+///
+/// This only exposes the [`Identifier`] api, so the owner can't be swapped out
+/// or rekeyed through the `&mut ThreadOwner` that [`with_thread_owner`] hands out.
+///
+/// ```compile_fail
+/// pui::with_thread_owner(|owner| owner.rekey());
+/// ```
+#[derive(PartialEq, Eq)]
+pub struct ThreadOwner(Runtime<ThreadOwnerIdAlloc, ThreadOwnerPool>);
+
+impl ThreadOwner {
+    /// Get a handle to the thread owner, this is the same as [`thread_owner`]
+    #[inline]
+    pub fn handle(&self) -> ThreadOwnerHandle { self.0.handle() }
+}
+
+unsafe impl Identifier for ThreadOwner {
+    type Handle = ThreadOwnerHandle;
+
+    #[inline]
+    fn handle(&self) -> Self::Handle { self.0.handle() }
+
+    #[inline]
+    fn owns(&self, handle: &Self::Handle) -> bool { self.0.owns(handle) }
+
+    #[inline]
+    fn check(&self, handle: &Self::Handle) -> Result<(), OwnershipError> { self.0.check(handle) }
+}
+
+forward_shared_identifier!([] ThreadOwner);
+
+/// A handle to a [`ThreadOwner`], see [`thread_owner`]
+pub type ThreadOwnerHandle = RuntimeHandle<ThreadOwnerIdAlloc>;
+
+thread_local! {
+    static THREAD_OWNER: (ThreadOwnerHandle, RefCell<ThreadOwner>) = {
+        let owner = ThreadOwnerIdAlloc::with_pool(ThreadOwnerPool);
+        // the owner lives as long as the thread, so it isn't a leak
+        #[cfg(feature = "track-leaks")]
+        owner.untrack();
+        (owner.handle(), RefCell::new(ThreadOwner(owner)))
+    };
+}
+
+/// A handle to the current thread's owner identifier
+///
+/// The owner is created the first time that it is used on a thread, and it is
+/// dropped when the thread exits, which returns its id to a thread local pool.
+/// The handle is thread local, so anything branded with it can't leave the thread.
+///
+/// ```
+/// use pui::cell::IdCell;
+///
+/// let cell = IdCell::new(pui::thread_owner(), 0);
+///
+/// pui::with_thread_owner(|owner| *cell.borrow_mut(owner) += 1);
+/// pui::with_thread_owner(|owner| assert_eq!(*cell.borrow(owner), 1));
+/// ```
+///
+/// # Panic
+///
+/// If the thread owner was already destroyed because the thread is exiting
+pub fn thread_owner() -> ThreadOwnerHandle { THREAD_OWNER.with(|(handle, _)| *handle) }
+
+/// Call `f` with the current thread's owner identifier
+///
+/// see [`thread_owner`] for details
+///
+/// # Panic
+///
+/// If the thread owner is already in use by an outer call to
+/// `with_thread_owner`, or if the thread owner was already destroyed
+/// because the thread is exiting
+pub fn with_thread_owner<F: FnOnce(&mut ThreadOwner) -> R, R>(f: F) -> R {
+    THREAD_OWNER.with(|(_, owner)| {
+        let mut owner = owner
+            .try_borrow_mut()
+            .expect("tried to use the thread owner while it is already in use");
+        f(&mut owner)
+    })
+}
+
+/// Call `f` with the current thread's owner identifier
+///
+/// returns `None` if the thread owner is already in use by an outer call to
+/// `with_thread_owner`, or if the thread owner was already destroyed
+/// because the thread is exiting
+pub fn try_with_thread_owner<F: FnOnce(&mut ThreadOwner) -> R, R>(f: F) -> Option<R> {
+    THREAD_OWNER
+        .try_with(|(_, owner)| Some(f(&mut *owner.try_borrow_mut().ok()?)))
+        .unwrap_or(None)
+}
@@ -64,7 +64,51 @@ impl<T> TypeHandle<T> {
     pub const fn new() -> Self { Self(PhantomData) }
 }
 
+/// Hooks into the lifecycle of the identifiers created by [`make_typeid`](make_typeid)
+///
+/// You can plug an observer into [`make_typeid`](make_typeid) with `observer = MyObserver;`.
+/// Every hook does nothing by default, and `()` is an observer that doesn't observe anything.
+///
+/// ```
+/// use pui::typeid::TypeIdObserver;
+/// use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+///
+/// static ACQUIRED: AtomicBool = AtomicBool::new(false);
+///
+/// struct Acquired;
+///
+/// impl TypeIdObserver<MyType> for Acquired {
+///     fn on_acquire() { ACQUIRED.store(true, Relaxed); }
+///
+///     fn on_release() { ACQUIRED.store(false, Relaxed); }
+/// }
+///
+/// pui::make_typeid! {
+///     type MyType;
+///     observer = Acquired;
+/// }
+///
+/// let ty = MyType::new();
+/// assert!(ACQUIRED.load(Relaxed));
+/// drop(ty);
+/// assert!(!ACQUIRED.load(Relaxed));
+/// ```
+pub trait TypeIdObserver<T> {
+    /// Called after the identifier was acquired
+    #[inline]
+    fn on_acquire() {}
+
+    /// Called when the identifier is dropped, before it can be acquired again
+    ///
+    /// This is never called for `once` identifiers, because they can't be released
+    #[inline]
+    fn on_release() {}
+}
+
+impl<T> TypeIdObserver<T> for () {}
+
 unsafe impl<T> crate::Handle for TypeHandle<T> {}
+unsafe impl<T: 'static> crate::any::Erasable for TypeHandle<T> {}
 unsafe impl<T> crate::Identifier for Type<T> {
     type Handle = TypeHandle<T>;
 
@@ -75,6 +119,8 @@ unsafe impl<T> crate::Identifier for Type<T> {
     fn owns(&self, _: &Self::Handle) -> bool { true }
 }
 
+forward_shared_identifier!([T] Type<T>);
+
 // common traits
 
 impl<T> Copy for TypeHandle<T> {}
@@ -28,9 +28,35 @@
 /// You can use `OnceThreadLocal::new()` to create a new thread local
 /// identifier instance if you are sure there are no other instances
 /// active, otherwise use `OnceThreadLocal::try_new()`
+///
+/// You can also add a [`TypeIdObserver`](crate::typeid::TypeIdObserver),
+/// which is told every time the identifier is acquired and released
+///
+/// ```
+/// # struct MyObserver;
+/// # impl<T> pui::typeid::TypeIdObserver<T> for MyObserver {}
+/// pui::make_typeid! {
+///     type MyType;
+///     observer = MyObserver;
+/// }
+/// ```
 #[macro_export]
 macro_rules! make_typeid {
     ($(#[$meta:meta])*$v:vis once type $ident:ident;) => {
+        $crate::make_typeid! {
+            $(#[$meta])*
+            $v once type $ident;
+            observer = ();
+        }
+    };
+    ($(#[$meta:meta])*$v:vis type $ident:ident;) => {
+        $crate::make_typeid! {
+            $(#[$meta])*
+            $v type $ident;
+            observer = ();
+        }
+    };
+    ($(#[$meta:meta])*$v:vis once type $ident:ident; observer = $observer:ty;) => {
         $(#[$meta])*
         $v struct $ident;
 
@@ -60,6 +86,7 @@ macro_rules! make_typeid {
                         $crate::macros::OnceFlag::new();
 
                     if make_typeid_FLAG.take() {
+                        <$observer as $crate::typeid::TypeIdObserver<Self>>::on_acquire();
                         unsafe {
                             $crate::macros::Option::Some($crate::typeid::Type::new_unchecked(
                                 Self,
@@ -73,7 +100,7 @@ macro_rules! make_typeid {
             }
         }
     };
-    ($(#[$meta:meta])*$v:vis type $ident:ident;) => {
+    ($(#[$meta:meta])*$v:vis type $ident:ident; observer = $observer:ty;) => {
         $(#[$meta])*
         $v struct $ident($crate::macros::MacroConstructed<Self>);
 
@@ -97,6 +124,8 @@ macro_rules! make_typeid {
                 pub fn new() -> $crate::typeid::Type<Self> {
                     unsafe {
                         if Self::__make_typeid_get_it().acquire() {
+                            $crate::macros::typeid_acquired::<Self>();
+                            <$observer as $crate::typeid::TypeIdObserver<Self>>::on_acquire();
                             $crate::typeid::Type::new_unchecked(
                                 Self($crate::macros::MacroConstructed::new()),
                                 $crate::typeid::TypeHandle::new(),
@@ -118,6 +147,8 @@ macro_rules! make_typeid {
                 pub fn try_new() -> $crate::macros::Option<$crate::typeid::Type<Self>> {
                     unsafe {
                         if Self::__make_typeid_get_it().try_acquire() {
+                            $crate::macros::typeid_acquired::<Self>();
+                            <$observer as $crate::typeid::TypeIdObserver<Self>>::on_acquire();
                             $crate::macros::Option::Some($crate::typeid::Type::new_unchecked(
                                 Self($crate::macros::MacroConstructed::new()),
                                 $crate::typeid::TypeHandle::new(),
@@ -132,6 +163,8 @@ macro_rules! make_typeid {
 
         impl $crate::macros::Drop for $ident {
             fn drop(&mut self) {
+                <$observer as $crate::typeid::TypeIdObserver<Self>>::on_release();
+                $crate::macros::typeid_released::<Self>();
                 unsafe {
                     Self::__make_typeid_get_it().release()
                 }
@@ -70,6 +70,8 @@ unsafe impl<T> crate::Identifier for Type<T> {
     fn owns(&self, _: &Self::Handle) -> bool { true }
 }
 
+forward_shared_identifier!([T] Type<T>);
+
 // common traits
 
 impl<T> Copy for TypeHandle<T> {}
@@ -0,0 +1,272 @@
+//! Branded UTF-8 strings and char boundaries
+//!
+//! A [`BrandedStr`] takes ownership of a [`OneShotIdentifier`](crate::OneShotIdentifier),
+//! and ties it to a string slice. Every [`CharBoundary`] that it hands out is guaranteed to
+//! be in bounds and on a char boundary of that string, so slicing the string with them
+//! doesn't need a bounds check or a char boundary check. This is the string analogue of
+//! [`BrandedSlice`](crate::slice::BrandedSlice).
+//!
+//! ```
+//! use pui::{scoped::Scoped, utf8::BrandedStr};
+//!
+//! Scoped::with(|ident| {
+//!     let text = BrandedStr::new("key = välue", ident);
+//!
+//!     let (eq, after_eq) = text.match_indices("=").next().unwrap();
+//!     assert_eq!(text[..eq].trim(), "key");
+//!     assert_eq!(text[after_eq..].trim(), "välue");
+//! })
+//! ```
+
+use core::{fmt, ops};
+
+use crate::OneShotIdentifier;
+
+/// A string slice that is branded by a [`OneShotIdentifier`](crate::OneShotIdentifier)
+///
+/// see module docs for details
+pub struct BrandedStr<'a, I> {
+    ident: I,
+    text: &'a str,
+}
+
+/// A byte offset that is guaranteed to be on a char boundary of
+/// the [`BrandedStr`] whose identifier owns `handle`
+///
+/// The end of the string is also a char boundary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CharBoundary<H> {
+    index: usize,
+    handle: H,
+}
+
+impl<H> CharBoundary<H> {
+    /// The underlying byte offset
+    #[inline]
+    pub const fn get(&self) -> usize { self.index }
+
+    /// The handle that brands this char boundary
+    #[inline]
+    pub const fn handle(&self) -> &H { &self.handle }
+}
+
+impl<'a, I: OneShotIdentifier> BrandedStr<'a, I> {
+    /// Brand the given string with the identifier
+    #[inline]
+    pub fn new(text: &'a str, ident: I) -> Self { Self { ident, text } }
+
+    /// The identifier that brands this string
+    #[inline]
+    pub fn identifier(&self) -> &I { &self.ident }
+
+    /// The underlying string
+    #[inline]
+    pub fn as_str(&self) -> &'a str { self.text }
+
+    /// The length of the string in bytes
+    #[inline]
+    pub fn len(&self) -> usize { self.text.len() }
+
+    /// Returns true if the string is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.text.is_empty() }
+
+    /// Check if the given char boundary was created by this string
+    #[inline]
+    pub fn owns(&self, boundary: &CharBoundary<I::Handle>) -> bool { self.ident.owns(&boundary.handle) }
+
+    #[inline]
+    fn brand(&self, index: usize) -> CharBoundary<I::Handle> {
+        CharBoundary {
+            index,
+            handle: self.ident.handle(),
+        }
+    }
+
+    fn offset(&self, boundary: &CharBoundary<I::Handle>) -> usize {
+        assert!(
+            self.ident.owns(&boundary.handle),
+            "tried to use a char boundary that is not owned by this string"
+        );
+
+        boundary.index
+    }
+
+    /// The start of the string
+    #[inline]
+    pub fn start(&self) -> CharBoundary<I::Handle> { self.brand(0) }
+
+    /// The end of the string
+    #[inline]
+    pub fn end(&self) -> CharBoundary<I::Handle> { self.brand(self.text.len()) }
+
+    /// Check if `index` is a char boundary, and if it is brand it
+    pub fn boundary(&self, index: usize) -> Option<CharBoundary<I::Handle>> {
+        if self.text.is_char_boundary(index) {
+            Some(self.brand(index))
+        } else {
+            None
+        }
+    }
+
+    /// An iterator over the chars of the string, and the char boundaries where they start
+    pub fn char_indices(&self) -> impl 'a + Iterator<Item = (CharBoundary<I::Handle>, char)>
+    where
+        I::Handle: 'a,
+    {
+        let handle = self.ident.handle();
+        self.text.char_indices().map(move |(index, c)| {
+            let boundary = CharBoundary {
+                index,
+                handle: handle.clone(),
+            };
+            (boundary, c)
+        })
+    }
+
+    /// An iterator over every char boundary of the string, including the end of the string
+    pub fn boundaries(&self) -> impl 'a + Iterator<Item = CharBoundary<I::Handle>>
+    where
+        I::Handle: 'a,
+    {
+        let end = self.end();
+        self.char_indices().map(|(boundary, _)| boundary).chain(Some(end))
+    }
+
+    /// The char that starts at the given char boundary, or `None`
+    /// if the boundary is the end of the string
+    ///
+    /// # Panic
+    ///
+    /// If the char boundary is not owned by this string
+    pub fn char_at(&self, boundary: CharBoundary<I::Handle>) -> Option<char> {
+        let index = self.offset(&boundary);
+        // This is safe because `boundary` was created by this string
+        unsafe { self.text.get_unchecked(index..) }.chars().next()
+    }
+
+    /// The char boundary after the given one, or `None` if the
+    /// given boundary is the end of the string
+    ///
+    /// # Panic
+    ///
+    /// If the char boundary is not owned by this string
+    pub fn next_boundary(&self, boundary: CharBoundary<I::Handle>) -> Option<CharBoundary<I::Handle>> {
+        let c = self.char_at(boundary.clone())?;
+        Some(CharBoundary {
+            index: boundary.index + c.len_utf8(),
+            handle: boundary.handle,
+        })
+    }
+
+    /// The char boundary before the given one, or `None` if the
+    /// given boundary is the start of the string
+    ///
+    /// # Panic
+    ///
+    /// If the char boundary is not owned by this string
+    pub fn prev_boundary(&self, boundary: CharBoundary<I::Handle>) -> Option<CharBoundary<I::Handle>> {
+        let index = self.offset(&boundary);
+        // This is safe because `boundary` was created by this string
+        let c = unsafe { self.text.get_unchecked(..index) }.chars().next_back()?;
+        Some(CharBoundary {
+            index: index - c.len_utf8(),
+            handle: boundary.handle,
+        })
+    }
+
+    /// Find the first occurrence of `needle`, and return the char boundary where it starts
+    pub fn find(&self, needle: &str) -> Option<CharBoundary<I::Handle>> {
+        self.text.find(needle).map(|index| self.brand(index))
+    }
+
+    /// Find the last occurrence of `needle`, and return the char boundary where it starts
+    pub fn rfind(&self, needle: &str) -> Option<CharBoundary<I::Handle>> {
+        self.text.rfind(needle).map(|index| self.brand(index))
+    }
+
+    /// Find the first char that matches the predicate, and return the char boundary where it starts
+    pub fn position<F: FnMut(char) -> bool>(&self, f: F) -> Option<CharBoundary<I::Handle>> {
+        self.text.find(f).map(|index| self.brand(index))
+    }
+
+    /// An iterator over the non-overlapping occurrences of `needle`, yields the
+    /// char boundaries where each occurrence starts and ends
+    pub fn match_indices<'b>(
+        &self,
+        needle: &'b str,
+    ) -> impl 'b + Iterator<Item = (CharBoundary<I::Handle>, CharBoundary<I::Handle>)>
+    where
+        'a: 'b,
+        I::Handle: 'b,
+    {
+        let handle = self.ident.handle();
+        self.text.match_indices(needle).map(move |(start, matched)| {
+            let start_boundary = CharBoundary {
+                index: start,
+                handle: handle.clone(),
+            };
+            let end_boundary = CharBoundary {
+                index: start + matched.len(),
+                handle: handle.clone(),
+            };
+            (start_boundary, end_boundary)
+        })
+    }
+
+    /// Divide the string into two at the given char boundary
+    ///
+    /// # Panic
+    ///
+    /// If the char boundary is not owned by this string
+    pub fn split_at(&self, mid: CharBoundary<I::Handle>) -> (&'a str, &'a str) {
+        let mid = self.offset(&mid);
+        // This is safe because `mid` was created by this string
+        unsafe { (self.text.get_unchecked(..mid), self.text.get_unchecked(mid..)) }
+    }
+
+    /// Get the substring between two char boundaries
+    ///
+    /// # Panic
+    ///
+    /// If either char boundary is not owned by this string, or if `start > end`
+    pub fn slice(&self, start: CharBoundary<I::Handle>, end: CharBoundary<I::Handle>) -> &'a str {
+        let start = self.offset(&start);
+        let end = self.offset(&end);
+
+        assert!(start <= end, "slice index starts at {} but ends at {}", start, end);
+
+        // This is safe because `start` and `end` were created by this string,
+        // and we checked that `start <= end`
+        unsafe { self.text.get_unchecked(start..end) }
+    }
+}
+
+impl<I: OneShotIdentifier> ops::Index<ops::Range<CharBoundary<I::Handle>>> for BrandedStr<'_, I> {
+    type Output = str;
+
+    #[inline]
+    fn index(&self, range: ops::Range<CharBoundary<I::Handle>>) -> &Self::Output { self.slice(range.start, range.end) }
+}
+
+impl<I: OneShotIdentifier> ops::Index<ops::RangeFrom<CharBoundary<I::Handle>>> for BrandedStr<'_, I> {
+    type Output = str;
+
+    #[inline]
+    fn index(&self, range: ops::RangeFrom<CharBoundary<I::Handle>>) -> &Self::Output { self.split_at(range.start).1 }
+}
+
+impl<I: OneShotIdentifier> ops::Index<ops::RangeTo<CharBoundary<I::Handle>>> for BrandedStr<'_, I> {
+    type Output = str;
+
+    #[inline]
+    fn index(&self, range: ops::RangeTo<CharBoundary<I::Handle>>) -> &Self::Output { self.split_at(range.end).0 }
+}
+
+impl<I> fmt::Debug for BrandedStr<'_, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.text.fmt(f) }
+}
+
+impl<I> fmt::Display for BrandedStr<'_, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.text.fmt(f) }
+}
@@ -0,0 +1,68 @@
+use pui::{
+    any::{AnyHandle, AnyIdentifier},
+    runtime::{Global, Runtime, RuntimeHandle},
+    typeid::TypeHandle,
+    Identifier,
+};
+
+pui::make_global_id_alloc! {
+    type TinyIdAlloc(TinyId) = [u8; 3];
+}
+
+pui::make_typeid! {
+    type MyType;
+}
+
+#[test]
+fn round_trip() {
+    let global = Runtime::new();
+    let tiny = TinyIdAlloc::new();
+    let ty = MyType::new();
+
+    assert_eq!(AnyHandle::new(global.handle()).downcast(), Some(global.handle()));
+    assert_eq!(AnyHandle::new(tiny.handle()).downcast(), Some(tiny.handle()));
+    assert_eq!(AnyHandle::new(ty.handle()).downcast(), Some(ty.handle()));
+
+    let handle = ty.any_handle();
+    assert!(handle.is::<TypeHandle<MyType>>());
+    assert!(handle.downcast::<RuntimeHandle<Global>>().is_none());
+    assert!(handle.type_name().contains("MyType"));
+}
+
+#[test]
+fn equality() {
+    let a = Runtime::new();
+    let b = Runtime::new();
+    let tiny = TinyIdAlloc::new();
+
+    assert_eq!(a.any_handle(), AnyHandle::new(a.handle()));
+    assert_ne!(a.any_handle(), b.any_handle());
+    assert_ne!(a.any_handle(), tiny.any_handle());
+
+    let mut handles = vec![b.any_handle(), tiny.any_handle(), a.any_handle(), b.any_handle()];
+    handles.sort();
+    handles.dedup();
+    assert_eq!(handles.len(), 3);
+
+    let set: std::collections::HashSet<_> = handles.iter().copied().collect();
+    assert!(set.contains(&a.any_handle()));
+    assert!(set.contains(&tiny.any_handle()));
+}
+
+#[test]
+fn owns() {
+    let a = Runtime::new();
+    let b = Runtime::new();
+    let tiny = TinyIdAlloc::new();
+
+    assert!(AnyIdentifier::owns(&a, &a.any_handle()));
+    assert!(!AnyIdentifier::owns(&a, &b.any_handle()));
+    assert!(!AnyIdentifier::owns(&a, &tiny.any_handle()));
+    assert!(AnyIdentifier::owns(&tiny, &tiny.any_handle()));
+
+    let idents: [&dyn AnyIdentifier; 2] = [&a, &tiny];
+    assert_eq!(idents.iter().filter(|ident| ident.owns(&tiny.any_handle())).count(), 1);
+
+    // `Identifier::owns` is still available
+    assert!(Identifier::owns(&a, &a.handle()));
+}
@@ -0,0 +1,185 @@
+use pui::{cell::IdCell, runtime::Runtime, Identifier};
+
+#[test]
+fn smoke() {
+    let mut owner = Runtime::new();
+    let other = Runtime::new();
+
+    let mut cell = IdCell::new(owner.handle(), 10);
+
+    assert!(cell.is_owned_by(&owner));
+    assert!(!cell.is_owned_by(&other));
+    assert!(cell.try_borrow(&other).is_none());
+
+    *cell.borrow_mut(&mut owner) += 1;
+    assert_eq!(*cell.borrow(&owner), 11);
+
+    *cell.get_mut() += 1;
+    assert_eq!(cell.into_inner(), 12);
+}
+
+#[test]
+fn borrow_mut2() {
+    let mut owner = Runtime::new();
+
+    let a = IdCell::new(owner.handle(), vec![1]);
+    let b = IdCell::new(owner.handle(), vec![2]);
+
+    let (a_ref, b_ref) = a.borrow_mut2(&b, &mut owner);
+    a_ref.append(b_ref);
+
+    assert_eq!(*a.borrow(&owner), [1, 2]);
+    assert!(b.borrow(&owner).is_empty());
+}
+
+#[test]
+#[should_panic = "tried to uniquely borrow overlapping cells"]
+fn borrow_mut2_same_cell() {
+    let mut owner = Runtime::new();
+    let a = IdCell::new(owner.handle(), 0);
+
+    let _ = a.borrow_mut2(&a, &mut owner);
+}
+
+#[test]
+#[should_panic = "tried to uniquely borrow overlapping cells"]
+fn borrow_mut2_nested_cell() {
+    let mut owner = Runtime::new();
+    let outer = IdCell::new(owner.handle(), (0, IdCell::new(owner.handle(), 0)));
+
+    let inner = unsafe { &(*outer.as_ptr()).1 };
+    let _ = outer.borrow_mut2(inner, &mut owner);
+}
+
+#[test]
+#[should_panic = "tried to access a cell that is not owned by this identifier"]
+fn foreign_cell() {
+    let owner = Runtime::new();
+    let cell = IdCell::new(Runtime::new().handle(), 0);
+
+    let _ = cell.borrow(&owner);
+}
+
+#[test]
+fn typeid() {
+    pui::make_typeid! {
+        type CellOwner;
+    }
+
+    let mut owner = CellOwner::new();
+    let cell = IdCell::new(owner.handle(), 'a');
+
+    *cell.borrow_mut(&mut owner) = 'b';
+    assert_eq!(*cell.borrow(&owner), 'b');
+}
+
+#[test]
+fn atomic() {
+    use pui::cell::{IdAtomicBool, IdAtomicI64};
+    use std::sync::atomic::Ordering::*;
+
+    let owner = Runtime::new();
+    let other = Runtime::new();
+
+    let count = IdAtomicI64::new(owner.handle(), 10);
+    let flag = IdAtomicBool::new(owner.handle(), false);
+
+    assert!(count.is_owned_by(&owner));
+    assert!(count.try_atomic(&other).is_none());
+
+    assert_eq!(count.fetch_add(&owner, 5, Relaxed), 10);
+    assert_eq!(count.fetch_min(&owner, 3, Relaxed), 15);
+    assert_eq!(count.compare_exchange(&owner, 3, -1, Relaxed, Relaxed), Ok(3));
+    assert_eq!(count.swap(&owner, 7, Relaxed), -1);
+    assert_eq!(count.fetch_update(&owner, Relaxed, Relaxed, |x| Some(x * 2)), Ok(7));
+    assert_eq!(count.load(&owner, Relaxed), 14);
+
+    assert!(!flag.fetch_or(&owner, true, Relaxed));
+    flag.store(&owner, false, Relaxed);
+    assert!(!flag.load(&owner, Relaxed));
+
+    assert_eq!(count.into_inner(), 14);
+}
+
+#[test]
+#[should_panic = "tried to access a cell that is not owned by this identifier"]
+fn foreign_atomic() {
+    use pui::cell::IdAtomicUsize;
+    use std::sync::atomic::Ordering::*;
+
+    let owner = Runtime::new();
+    let count = IdAtomicUsize::new(Runtime::new().handle(), 0);
+
+    count.fetch_add(&owner, 1, Relaxed);
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn shared_identifier() {
+    use std::{rc::Rc, sync::Arc};
+
+    let mut owner = Runtime::new();
+    let cell = IdCell::new(owner.handle(), 10);
+    *cell.borrow_mut(&mut owner) += 1;
+
+    let rc = Rc::new(owner);
+    let rc2 = rc.clone();
+    assert!(cell.is_owned_by(&rc));
+    assert_eq!((*cell.borrow(&rc), *cell.borrow(&rc2)), (11, 11));
+
+    drop(rc2);
+    let arc = Arc::new(Rc::try_unwrap(rc).unwrap());
+    assert_eq!(cell.try_borrow(&arc), Some(&11));
+    assert!(cell.try_borrow(&Arc::new(Runtime::new())).is_none());
+
+    let mut owner = Arc::try_unwrap(arc).unwrap();
+    *cell.borrow_mut(&mut owner) += 1;
+    assert_eq!(*cell.borrow(&owner), 12);
+}
+
+#[test]
+fn ref_shared_identifier() {
+    fn read<S: pui::SharedIdentifier<Handle = H>, H>(cell: &IdCell<H, i32>, ident: S) -> i32 { *cell.borrow(&ident) }
+
+    let owner = Runtime::new();
+    let cell = IdCell::new(owner.handle(), 12);
+
+    assert_eq!(read(&cell, &owner), 12);
+    assert_eq!(*cell.borrow(&&owner), 12);
+    assert!(cell.try_borrow(&&Runtime::new()).is_none());
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[should_panic = "tried to access a cell that is not owned by this identifier"]
+fn foreign_shared_identifier() {
+    let owner = std::sync::Arc::new(Runtime::new());
+    let cell = IdCell::new(Runtime::new().handle(), 0);
+
+    cell.borrow(&owner);
+}
+
+#[test]
+fn composite_owner() {
+    pui::make_global_id_alloc! {
+        type DocAlloc(DocId) = u32;
+    }
+
+    let document = DocAlloc::new();
+    let session = Runtime::new();
+    let mut owner = (document, session);
+
+    let both = IdCell::new(owner.handle(), 0);
+    let document_only = IdCell::new(owner.0.handle(), 0);
+
+    *both.borrow_mut(&mut owner) += 1;
+    *document_only.borrow_mut(&mut owner.0) += 1;
+    assert_eq!(*both.borrow(&owner), 1);
+
+    let other = (owner.0, Runtime::new());
+    assert!(!both.is_owned_by(&other));
+
+    // the error names the element that doesn't own its part of the handle
+    let error = other.check(both.handle()).unwrap_err();
+    assert_eq!(error.identifier_type(), "pui::runtime::Runtime");
+}
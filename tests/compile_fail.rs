@@ -7,7 +7,12 @@ fn compile_fail() {
     cases.compile_fail("tests/compile_fail/*.rs");
     cases.compile_fail("tests/compile_fail/scoped/*.rs");
 
+    if cfg!(feature = "derive") {
+        cases.compile_fail("tests/compile_fail/derive/*.rs");
+    }
+
     if cfg!(feature = "std") {
+        cases.compile_fail("tests/compile_fail/std/*.rs");
         cases.compile_fail("tests/compile_fail/thread_local/*.rs");
     } else {
         cases.compile_fail("tests/compile_fail/no_std/*/*.rs");
@@ -0,0 +1,10 @@
+use pui::{cell::IdCell, runtime::Runtime};
+use std::sync::Arc;
+
+fn main() {
+    let mut owner = Arc::new(Runtime::new());
+    let cell = IdCell::new(owner.handle(), 0);
+
+    // shared identifiers only grant shared access
+    *cell.borrow_mut(&mut owner) += 1;
+}
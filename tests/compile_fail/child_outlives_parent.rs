@@ -0,0 +1,8 @@
+use pui::runtime::Runtime;
+
+fn main() {
+    let parent = Runtime::new();
+    let child = parent.child();
+    drop(parent);
+    drop(child);
+}
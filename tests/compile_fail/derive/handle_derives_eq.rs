@@ -0,0 +1,5 @@
+// `derive(Handle)` implements `Clone`, `PartialEq`, and `Eq` itself
+#[derive(pui::Handle, Clone, PartialEq, Eq)]
+struct DocHandle(pui::runtime::RuntimeHandle);
+
+fn main() {}
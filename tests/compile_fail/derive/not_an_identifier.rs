@@ -0,0 +1,14 @@
+#[derive(pui::Identifier, PartialEq, Eq)]
+struct NotAnOwner(u32);
+
+#[derive(pui::Identifier, PartialEq, Eq)]
+struct TwoOwners(pui::runtime::Runtime, pui::runtime::Runtime);
+
+#[derive(pui::Handle)]
+struct NotAHandle(u32);
+
+#[derive(pui::Identifier, PartialEq, Eq)]
+#[identifier(handle = NotAHandle)]
+struct WrongHandle(pui::runtime::Runtime);
+
+fn main() {}
@@ -0,0 +1,7 @@
+use pui::runtime::Runtime;
+
+fn main() {
+    // a pooled runtime may reuse its id, so it can't have children
+    let parent = Runtime::with_pool(None);
+    let _child = parent.child();
+}
@@ -0,0 +1,7 @@
+use pui::{runtime::Runtime, slice::BrandedSlice};
+
+fn main() {
+    // a pooled runtime may reuse its id, so it can't brand a slice
+    let slice = BrandedSlice::new(&[1, 2, 3], Runtime::with_pool(None));
+    let _ = slice.idx(0);
+}
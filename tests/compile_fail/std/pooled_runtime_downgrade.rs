@@ -0,0 +1,10 @@
+pui::make_global_id_alloc! {
+    type TrackedAlloc(TrackedId) = u32;
+    track_liveness;
+}
+
+fn main() {
+    // a pooled runtime may reuse its id, so a weak reference to it could come back to life
+    let runtime = TrackedAlloc::with_pool(None);
+    let _weak = runtime.downgrade();
+}
@@ -0,0 +1,10 @@
+use pui::{cell::IdCell, runtime::Runtime};
+use std::cell::Cell;
+
+fn main() {
+    fn assert_sync<T: Sync>(_: &T) {}
+
+    let owner = Runtime::new();
+    let cell = IdCell::new(owner.handle(), Cell::new(0));
+    assert_sync(&cell);
+}
@@ -0,0 +1,12 @@
+use pui::cell::IdCell;
+
+pui::make_global_id_alloc_tl! {
+    type Foo(FooId) = u8;
+}
+
+fn main() {
+    let owner = Foo::new();
+    let cell = IdCell::new(owner.handle(), 0);
+
+    std::thread::spawn(move || cell.into_inner());
+}
@@ -0,0 +1,14 @@
+use pui::cell::IdCell;
+
+pui::make_typeid_tl! {
+    type Owner;
+}
+
+fn main() {
+    let mut owner = Owner::new();
+    let cell = IdCell::new(owner.handle(), 0);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| *cell.borrow_mut(&mut owner) += 1);
+    });
+}
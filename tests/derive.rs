@@ -0,0 +1,88 @@
+#![cfg(feature = "derive")]
+
+use pui::{
+    cell::IdCell,
+    runtime::{Runtime, RuntimeHandle},
+    typeid::Type,
+    Handle, Identifier,
+};
+
+pui::make_global_id_alloc! {
+    type DocAlloc(DocId) = u32;
+}
+
+#[derive(Identifier, PartialEq, Eq)]
+struct DocOwner(Runtime<DocAlloc>);
+
+#[derive(Identifier, PartialEq, Eq)]
+struct Named {
+    owner: Runtime,
+}
+
+#[derive(Identifier, PartialEq, Eq)]
+struct Generic<I>(I);
+
+#[derive(Handle, Copy, Debug)]
+struct DocHandle(RuntimeHandle<DocAlloc>);
+
+#[derive(Handle)]
+struct NamedHandle<H> {
+    handle: H,
+}
+
+#[derive(Identifier, PartialEq, Eq)]
+#[identifier(handle = DocHandle)]
+struct WrappedDocOwner(Runtime<DocAlloc>);
+
+#[derive(Identifier, PartialEq, Eq)]
+#[identifier(handle = NamedHandle<RuntimeHandle>)]
+struct WrappedNamed {
+    owner: Runtime,
+}
+
+#[test]
+fn forwards() {
+    let a = DocOwner(DocAlloc::new());
+    let b = DocOwner(DocAlloc::new());
+
+    assert!(a.owns(&a.handle()));
+    assert!(!a.owns(&b.handle()));
+    assert_eq!(a.handle(), a.0.handle());
+    assert_eq!(
+        a.check(&b.handle()).unwrap_err().identifier_type(),
+        "pui::runtime::Runtime<derive::DocAlloc>"
+    );
+
+    let named = Named { owner: Runtime::new() };
+    assert!(named.owns(&named.owner.handle()));
+
+    pui::make_typeid! {
+        type MyType;
+    }
+
+    let generic = Generic::<Type<MyType>>(MyType::new());
+    assert!(generic.owns(&generic.handle()));
+}
+
+#[test]
+fn handle_newtype() {
+    let a = WrappedDocOwner(DocAlloc::new());
+    let b = WrappedDocOwner(DocAlloc::new());
+
+    assert_eq!(a.handle(), DocHandle(a.0.handle()));
+    assert!(a.owns(&a.handle()));
+    assert!(!a.owns(&b.handle()));
+    assert_eq!(a.handle().clone(), a.handle());
+    assert_ne!(a.handle(), b.handle());
+    assert!(a.check(&a.handle()).is_ok());
+    assert_eq!(
+        a.check(&b.handle()).unwrap_err().identifier_type(),
+        "pui::runtime::Runtime<derive::DocAlloc>"
+    );
+
+    let mut named = WrappedNamed { owner: Runtime::new() };
+    let cell = IdCell::new(named.handle(), 0);
+    *cell.borrow_mut(&mut named) += 1;
+    assert_eq!(*cell.borrow(&named), 1);
+    assert!(!cell.is_owned_by(&WrappedNamed { owner: Runtime::new() }));
+}
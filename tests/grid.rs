@@ -0,0 +1,67 @@
+#![cfg(any(feature = "std", feature = "alloc"))]
+
+use pui::{grid::BrandedGrid, runtime::Runtime, scoped::Scoped};
+
+#[test]
+fn smoke() {
+    Scoped::with(|ident| {
+        let mut grid = BrandedGrid::from_vec(ident, 2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+
+        assert_eq!((grid.height(), grid.width()), (2, 3));
+        assert!(grid.row(2).is_none());
+        assert!(grid.col(3).is_none());
+        assert!(grid.coord(1, 3).is_none());
+
+        let row = grid.row(1).unwrap();
+        let col = grid.col(2).unwrap();
+        let coord = grid.at(row, col);
+        assert_eq!(coord.get(), (1, 2));
+        assert_eq!(grid[coord], 6);
+
+        grid[row][0] = 40;
+        assert_eq!(grid[grid.coord(1, 0).unwrap()], 40);
+
+        let total: i32 = grid.coords().map(|coord| grid[coord]).sum();
+        assert_eq!(total, 57);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.cols().count(), 3);
+    })
+}
+
+#[test]
+fn from_vec_wrong_size() {
+    assert!(BrandedGrid::from_vec(Runtime::new(), 2, 3, vec![0; 5]).is_none());
+}
+
+#[test]
+fn neighbours() {
+    let grid = BrandedGrid::from_fn(Runtime::new(), 3, 3, |row, col| (row, col));
+
+    let corner = grid.coord(0, 0).unwrap();
+    let mut around: Vec<_> = grid.neighbours4(corner).map(|coord| grid[coord]).collect();
+    around.sort();
+    assert_eq!(around, [(0, 1), (1, 0)]);
+    assert_eq!(grid.neighbours8(corner).count(), 3);
+
+    let center = grid.coord(1, 1).unwrap();
+    assert_eq!(grid.neighbours4(center).count(), 4);
+    assert_eq!(grid.neighbours8(center).count(), 8);
+
+    let edge = grid.coord(2, 1).unwrap();
+    assert_eq!(grid.neighbours8(edge).map(|coord| grid[coord]).collect::<Vec<_>>(), [
+        (1, 0),
+        (1, 1),
+        (1, 2),
+        (2, 0),
+        (2, 2)
+    ]);
+}
+
+#[test]
+#[should_panic = "tried to use a coordinate that is not owned by this grid"]
+fn foreign_coord() {
+    let a = BrandedGrid::from_fn(Runtime::new(), 3, 3, |_, _| 0);
+    let b = BrandedGrid::from_fn(Runtime::new(), 1, 1, |_, _| 0);
+
+    let _ = b[a.coord(2, 2).unwrap()];
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "track-leaks")]
+
+use pui::{
+    leaks::{self, HeldKind},
+    runtime::Runtime,
+    Identifier,
+};
+
+// everything is in one test, because the held identifiers are tracked for the whole process
+#[test]
+fn leaks() {
+    pui::make_typeid! {
+        type Leaked;
+    }
+
+    leaks::assert_none_held();
+
+    let runtime = Runtime::new();
+    let held = leaks::held();
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].kind(), HeldKind::Runtime);
+    assert_eq!(held[0].type_name(), core::any::type_name::<Runtime>());
+    assert_eq!(held[0].thread_id(), std::thread::current().id());
+
+    let id = held[0].id().unwrap().to_string();
+    assert!(id.starts_with("GlobalId("));
+    assert!(format!("{:?}", runtime).contains(&id));
+
+    let mut runtime = runtime;
+    runtime.rekey();
+    let rekeyed = leaks::held()[0].id().unwrap().to_string();
+    assert_ne!(rekeyed, id);
+    assert!(format!("{:?}", runtime).contains(&rekeyed));
+    assert!(leaks::held()[0]
+        .to_string()
+        .contains(&format!("with the id {} was created", rekeyed)));
+    drop(runtime);
+    leaks::assert_none_held();
+
+    // the thread owner is held until the thread exits, so it isn't recorded
+    pui::with_thread_owner(|owner| assert!(owner.owns(&pui::thread_owner())));
+    leaks::assert_none_held();
+
+    std::thread::Builder::new()
+        .name("leaker".into())
+        .spawn(|| core::mem::forget(Leaked::new()))
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let held = leaks::held();
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].kind(), HeldKind::TypeId);
+    assert_eq!(held[0].id(), None);
+    assert_eq!(held[0].thread_name(), Some("leaker"));
+    assert!(Leaked::try_new().is_none());
+
+    let message = *std::panic::catch_unwind(leaks::assert_none_held)
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+
+    assert!(message.starts_with("1 identifier(s) are still held"));
+    assert!(message.contains(&format!(
+        "`{}` was created on thread `leaker`",
+        core::any::type_name::<Leaked>()
+    )));
+}
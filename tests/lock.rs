@@ -0,0 +1,117 @@
+use pui::{cell::IdCell, lock::IdSpinLock, runtime::Runtime};
+
+#[test]
+fn spin_lock() {
+    let lock = IdSpinLock::new(Runtime::new());
+    let cells: Vec<_> = (0..4).map(|i| IdCell::new(lock.handle(), i)).collect();
+
+    let mut owner = lock.lock();
+    assert!(lock.try_lock().is_none());
+
+    for cell in &cells {
+        *cell.borrow_mut(&mut *owner) *= 2;
+    }
+
+    assert_eq!(cells.iter().map(|cell| *cell.borrow(&*owner)).sum::<i32>(), 12);
+
+    drop(owner);
+    assert!(lock.try_lock().is_some());
+}
+
+#[test]
+fn rekey_through_guard() {
+    let lock = IdSpinLock::new(Runtime::new());
+    let cell = IdCell::new(lock.handle(), 0);
+
+    lock.lock().rekey();
+
+    // the cached handle belongs to the old id, so the cell can't be accessed anymore
+    assert_eq!(lock.handle(), *cell.handle());
+    assert!(!cell.is_owned_by(&*lock.lock()));
+    assert!(cell.try_borrow(&*lock.lock()).is_none());
+
+    let fresh = IdCell::new(lock.lock().handle(), 1);
+    assert_eq!(*fresh.borrow(&*lock.lock()), 1);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn mutex() {
+    use pui::lock::IdMutex;
+
+    let lock = IdMutex::new(Runtime::new());
+    let a = IdCell::new(lock.handle(), 1);
+    let b = IdCell::new(lock.handle(), 2);
+
+    {
+        let mut owner = lock.lock();
+        assert!(lock.try_lock().is_none());
+        *a.borrow_mut(&mut *owner) += *b.borrow(&*owner);
+    }
+
+    let owner = lock.into_inner();
+    assert_eq!(*a.borrow(&owner), 3);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn rw_lock() {
+    use pui::lock::IdRwLock;
+
+    let lock = IdRwLock::new(Runtime::new());
+    let cell = IdCell::new(lock.handle(), String::from("a"));
+
+    {
+        let first = lock.read();
+        let second = lock.try_read().unwrap();
+        assert!(lock.try_write().is_none());
+        assert_eq!(cell.borrow(&*first), cell.borrow(&*second));
+    }
+
+    cell.borrow_mut(&mut *lock.write()).push('b');
+    assert_eq!(*cell.borrow(&*lock.read()), "ab");
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn mutex_ignores_poison() {
+    use pui::lock::IdMutex;
+    use std::sync::Arc;
+
+    let lock = Arc::new(IdMutex::new(Runtime::new()));
+
+    let thread_lock = lock.clone();
+    let _ = std::thread::spawn(move || {
+        let _owner = thread_lock.lock();
+        panic!("poison the lock");
+    })
+    .join();
+
+    let cell = IdCell::new(lock.handle(), 0);
+    assert_eq!(*cell.borrow(&*lock.lock()), 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn share_cells_between_threads() {
+    use pui::lock::IdMutex;
+
+    let lock = IdMutex::new(Runtime::new());
+    let cells: Vec<_> = (0..3).map(|_| IdCell::new(lock.handle(), 0)).collect();
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    let mut owner = lock.lock();
+                    for cell in &cells {
+                        *cell.borrow_mut(&mut *owner) += 1;
+                    }
+                }
+            });
+        }
+    });
+
+    let owner = lock.lock();
+    assert!(cells.iter().all(|cell| *cell.borrow(&*owner) == 400));
+}
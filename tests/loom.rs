@@ -0,0 +1,206 @@
+//! Model checks for the flags that back the global macros
+//!
+//! These only run under `loom`:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+#![cfg(loom)]
+
+use loom::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering::*},
+        Arc,
+    },
+    thread,
+};
+use pui::macros::{AtomicSlot, GlobalCell, InitFlag, OnceFlag, ResettableOnceFlag};
+
+#[test]
+fn once_flag_is_taken_once() {
+    loom::model(|| {
+        let flag = Arc::new(OnceFlag::new());
+        let taken = Arc::new(AtomicUsize::new(0));
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let flag = flag.clone();
+                let taken = taken.clone();
+                thread::spawn(move || {
+                    if flag.take() {
+                        taken.fetch_add(1, Relaxed);
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(taken.load(Relaxed), 1);
+        assert!(!flag.take());
+    })
+}
+
+#[test]
+fn resettable_once_flag_is_exclusive() {
+    loom::model(|| {
+        let flag = Arc::new(ResettableOnceFlag::new());
+        let value = Arc::new(UnsafeCell::new(0));
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let flag = flag.clone();
+                let value = value.clone();
+                thread::spawn(move || {
+                    assert!(flag.acquire());
+                    // loom reports a data race if two threads get here at the same time
+                    value.with_mut(|value| unsafe { *value += 1 });
+                    flag.release();
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(value.with(|value| unsafe { *value }), 2);
+    })
+}
+
+#[test]
+fn resettable_once_flag_wakes_up_waiters() {
+    // three threads are too many to check every interleaving in a reasonable time
+    let mut model = loom::model::Builder::new();
+    model.preemption_bound = Some(3);
+
+    model.check(|| {
+        let flag = Arc::new(ResettableOnceFlag::new());
+
+        // hold the flag, so that the other threads have to wait for it
+        assert!(flag.acquire());
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let flag = flag.clone();
+                thread::spawn(move || {
+                    assert!(flag.acquire());
+                    flag.release();
+                })
+            })
+            .collect();
+
+        flag.release();
+
+        // if a wakeup is missed, then one of these threads never finishes, and loom reports a deadlock
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(flag.try_acquire());
+    })
+}
+
+#[test]
+fn init_flag_hands_off_value() {
+    loom::model(|| {
+        let flag = Arc::new(InitFlag::new());
+        let slot = Arc::new(UnsafeCell::new(None::<u32>));
+
+        let put = {
+            let flag = flag.clone();
+            let slot = slot.clone();
+            thread::spawn(move || {
+                if flag.start_init() {
+                    slot.with_mut(|slot| unsafe { *slot = Some(10) });
+                    flag.finish_init();
+                    true
+                } else {
+                    false
+                }
+            })
+        };
+
+        let take = {
+            let flag = flag.clone();
+            let slot = slot.clone();
+            thread::spawn(move || {
+                if flag.start_take() {
+                    let value = slot.with_mut(|slot| unsafe { (*slot).take() });
+                    flag.finish_take();
+                    value
+                } else {
+                    None
+                }
+            })
+        };
+
+        assert!(put.join().unwrap());
+
+        match take.join().unwrap() {
+            Some(value) => {
+                assert_eq!(value, 10);
+                assert!(flag.start_init());
+            }
+            None => {
+                assert!(flag.start_take());
+                assert_eq!(slot.with_mut(|slot| unsafe { (*slot).take() }), Some(10));
+            }
+        }
+    })
+}
+
+#[test]
+fn global_cell_is_initialized_once() {
+    loom::model(|| {
+        let cell = Arc::new(GlobalCell::new());
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let threads: Vec<_> = (0..2)
+            .map(|i| {
+                let cell = cell.clone();
+                let count = count.clone();
+                thread::spawn(move || {
+                    *cell.get_or_init(|| {
+                        count.fetch_add(1, Relaxed);
+                        i
+                    })
+                })
+            })
+            .collect();
+
+        let values: Vec<_> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+
+        assert_eq!(count.load(Relaxed), 1);
+        assert_eq!(values[0], values[1]);
+    })
+}
+
+#[test]
+fn atomic_slot_holds_one_value() {
+    loom::model(|| {
+        let slot = Arc::new(AtomicSlot::new());
+
+        let threads: Vec<_> = (0..2)
+            .map(|i| {
+                let slot = slot.clone();
+                thread::spawn(move || slot.try_put(i).is_ok())
+            })
+            .collect();
+
+        let taken = slot.take();
+        let put = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .filter(|&put| put)
+            .count();
+        let left = slot.take();
+
+        // every value that was put into the slot must come out exactly once
+        assert_eq!(put, usize::from(taken.is_some()) + usize::from(left.is_some()));
+        assert!(slot.take().is_none());
+    })
+}
@@ -0,0 +1,76 @@
+use pui::{runtime::Runtime, Identifier, OwnershipError};
+
+#[test]
+fn check() {
+    let owner = Runtime::new();
+    let other = Runtime::new();
+
+    assert_eq!(owner.check(&owner.handle()), Ok(()));
+
+    let err = owner.check(&other.handle()).unwrap_err();
+    assert_eq!(err.identifier_type(), core::any::type_name::<Runtime>());
+    assert!(err.handle_type().contains("RuntimeHandle"));
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    {
+        let details = format!("identifier: {:?}, handle: {:?}", owner, other.handle());
+        assert_eq!(err.details(), Some(&*details));
+        assert_eq!(
+            err,
+            OwnershipError::new::<Runtime>().with_details(&owner, &other.handle())
+        );
+    }
+}
+
+#[test]
+fn check_child() {
+    let parent = Runtime::new();
+    let child = parent.child();
+    let other = parent.child();
+
+    let err = child.check(&other.handle()).unwrap_err();
+    assert!(err.identifier_type().contains("ChildRuntime"));
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    {
+        let details = err.details().unwrap();
+        assert!(details.starts_with("identifier: Runtime("));
+        assert!(details.contains("handle: RuntimeHandle { inner: "));
+    }
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn with_details() {
+    let err = OwnershipError::new::<Runtime>().with_details(&1, &2);
+    assert_eq!(err.details(), Some("identifier: 1, handle: 2"));
+    assert!(err.to_string().ends_with("(identifier: 1, handle: 2)"));
+}
+
+#[test]
+fn assert_owns() {
+    let owner = Runtime::new();
+    pui::assert_owns!(owner, owner.handle());
+}
+
+#[test]
+fn assert_owns_message() {
+    let owner = Runtime::new();
+    let other = Runtime::new();
+
+    let message = *std::panic::catch_unwind(|| pui::assert_owns!(owner, other.handle()))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+
+    assert!(message.starts_with(&format!("`{}` doesn't own the `", core::any::type_name::<Runtime>())));
+    assert!(message.ends_with(&format!("(identifier: {:?}, handle: {:?})", owner, other.handle())));
+}
+
+#[test]
+#[should_panic = "runtime 0 doesn't match: `pui::runtime::Runtime` doesn't own the `"]
+fn assert_owns_custom_message() {
+    let owner = Runtime::new();
+    let other = Runtime::new();
+    pui::assert_owns!(owner, other.handle(), "runtime {} doesn't match", 0);
+}
@@ -40,3 +40,331 @@ fn exhaust_u8() {
         }
     }
 }
+
+#[test]
+#[cfg(feature = "std")]
+fn rekey() {
+    use pui::{runtime::Runtime, Identifier};
+
+    pui::make_global_id_alloc! {
+        type TinyIdAlloc(TinyId) = u8;
+    }
+
+    pui::make_global_pool! {
+        stack TinyPool(TinyId);
+    }
+
+    let mut a = TinyIdAlloc::with_pool(TinyPool);
+    let old = a.handle();
+
+    a.rekey_with_id_alloc(&mut TinyIdAlloc);
+    assert!(!a.owns(&old));
+    assert!(a.owns(&a.handle()));
+
+    // the old id is discarded, so it never goes back to the pool
+    let b = Runtime::with_id_alloc_and_pool(&mut TinyIdAlloc, TinyPool);
+    assert!(!b.owns(&old));
+    assert!(!b.owns(&a.handle()));
+
+    while TinyIdAlloc::try_new().is_some() {}
+
+    let current = a.handle();
+    assert!(!a.try_rekey_with_id_alloc(&mut TinyIdAlloc));
+    assert!(a.owns(&current));
+
+    let mut global = Runtime::new();
+    let old = global.handle();
+    global.rekey();
+    assert!(global.try_rekey());
+    assert!(!global.owns(&old));
+}
+
+#[test]
+fn child() {
+    use pui::{runtime::Runtime, Identifier};
+
+    pui::make_global_id_alloc! {
+        type TinyIdAlloc(TinyId) = u8;
+    }
+
+    let document = Runtime::with_id_alloc(&mut TinyIdAlloc);
+    let other = Runtime::with_id_alloc(&mut TinyIdAlloc);
+    let section = document.child_with_id_alloc(&mut TinyIdAlloc);
+    let paragraph = section.child_with_id_alloc(&mut TinyIdAlloc);
+    let sibling = document.child_with_id_alloc(&mut TinyIdAlloc);
+
+    assert_eq!(*section.parent(), document.handle());
+    assert_eq!(*paragraph.handle().parent(), section.handle());
+
+    assert!(document.owns_descendant(&document.handle()));
+    assert!(document.owns_descendant(&section.handle()));
+    assert!(document.owns_descendant(&paragraph.handle()));
+    assert!(section.owns_descendant(&paragraph.handle()));
+    assert!(paragraph.owns_descendant(&paragraph.handle()));
+
+    assert!(!section.owns_descendant(&document.handle()));
+    assert!(!paragraph.owns_descendant(&section.handle()));
+    assert!(!sibling.owns_descendant(&paragraph.handle()));
+    assert!(!other.owns_descendant(&paragraph.handle()));
+
+    assert!(section.owns(&section.handle()));
+    assert!(!sibling.owns(&section.handle()));
+    assert!(section.owns(paragraph.handle().parent()));
+    assert_ne!(section.handle(), sibling.handle());
+
+    let cell = pui::cell::IdCell::new(paragraph.handle(), 0);
+    assert!(cell.is_owned_by(&paragraph));
+    assert!(document.owns_descendant(cell.handle()));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn randomized() {
+    use pui::{runtime::Randomized, Identifier};
+    use std::collections::HashSet;
+
+    let runtimes: Vec<_> = (0..1000).map(|_| Randomized::new()).collect();
+    let ids: HashSet<_> = runtimes.iter().map(|runtime| runtime.handle().0).collect();
+    assert_eq!(ids.len(), runtimes.len());
+
+    for (i, runtime) in runtimes.iter().enumerate() {
+        assert!(runtime.owns(&runtime.handle()));
+        assert!(!runtime.owns(&runtimes[(i + 1) % runtimes.len()].handle()));
+    }
+
+    // the ids are opaque, so read them back from the debug output
+    let value = |runtime: &pui::runtime::Runtime<Randomized>| -> u64 {
+        let debug = format!("{:?}", runtime.handle().0);
+        debug["RandomizedId(".len()..debug.len() - 1].parse().unwrap()
+    };
+
+    // consecutive ids aren't consecutive numbers
+    let sequential = runtimes
+        .windows(2)
+        .filter(|pair| value(&pair[1]).wrapping_sub(value(&pair[0])) == 1)
+        .count();
+    assert!(sequential < 10);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn liveness() {
+    use pui::runtime::{IdAllocObserver, Runtime};
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+    pui::make_global_id_alloc! {
+        type TinyIdAlloc(TinyId) = u8;
+        track_liveness;
+    }
+
+    pui::make_global_pool! {
+        one TinyPool(TinyId);
+    }
+
+    let a = TinyIdAlloc::with_pool(TinyPool);
+    let mut b = TinyIdAlloc::new();
+    let a_handle = a.handle();
+    let b_handle = b.handle();
+    let b_weak = b.downgrade();
+
+    assert!(a_handle.is_alive());
+    assert!(b_handle.is_alive());
+    assert!(b_weak.is_alive());
+    assert_eq!(b_weak.handle(), b_handle);
+
+    drop(a);
+    assert!(!a_handle.is_alive());
+
+    b.rekey_with_id_alloc(&mut TinyIdAlloc);
+    assert!(!b_handle.is_alive());
+    assert!(!b_weak.is_alive());
+    assert!(b.handle().is_alive());
+    assert_ne!(b.downgrade(), b_weak);
+    assert!(b.downgrade().is_alive());
+
+    // the id was reused, so the old handle is owned again
+    let c = TinyIdAlloc::with_pool(TinyPool);
+    assert_eq!(c.handle(), a_handle);
+    assert!(a_handle.is_alive());
+
+    static RELEASED: AtomicUsize = AtomicUsize::new(0);
+
+    struct CountReleased;
+
+    impl IdAllocObserver<ObservedId> for CountReleased {
+        fn on_release(_: &ObservedId) { RELEASED.fetch_add(1, Relaxed); }
+    }
+
+    pui::make_global_id_alloc! {
+        type ObservedIdAlloc(ObservedId) = u8;
+        observer = CountReleased;
+        track_liveness;
+    }
+
+    let observed = Runtime::with_id_alloc(&mut ObservedIdAlloc);
+    let handle = observed.handle();
+    assert!(handle.is_alive());
+    drop(observed);
+    assert!(!handle.is_alive());
+    assert_eq!(RELEASED.load(Relaxed), 1);
+}
+
+#[test]
+#[cfg(feature = "debug-names")]
+fn debug_names() {
+    use pui::runtime::Runtime;
+
+    let named = Runtime::new_named("parser-arena");
+    let handle = named.handle();
+    let unnamed = Runtime::new();
+
+    assert_eq!(
+        format!("{:?}", named),
+        format!("Runtime(\"parser-arena\", {:?})", handle.0)
+    );
+    assert_eq!(
+        format!("{:?}", handle),
+        format!("RuntimeHandle {{ name: \"parser-arena\", inner: {:?} }}", handle.0)
+    );
+    assert!(!format!("{:?}", unnamed).contains("parser-arena"));
+
+    let mut named = named;
+    named.rekey();
+    assert_eq!(
+        format!("{:?}", named.handle()),
+        format!(
+            "RuntimeHandle {{ name: \"parser-arena\", inner: {:?} }}",
+            named.handle().0
+        )
+    );
+    assert_eq!(
+        format!("{:?}", handle),
+        format!("RuntimeHandle {{ inner: {:?} }}", handle.0)
+    );
+
+    drop(named);
+    assert_eq!(
+        format!("{:?}", handle),
+        format!("RuntimeHandle {{ inner: {:?} }}", handle.0)
+    );
+}
+
+#[test]
+#[cfg(all(feature = "stats", feature = "std"))]
+fn stats() {
+    pui::make_global_id_alloc! {
+        type TinyIdAlloc(TinyId) = u8;
+    }
+
+    pui::make_global_pool! {
+        stack TinyPool(TinyId);
+    }
+
+    let stats = TinyIdAlloc::stats();
+    assert_eq!(stats.capacity(), 255);
+
+    let a = TinyIdAlloc::new();
+    let b = TinyIdAlloc::with_pool(TinyPool);
+    assert_eq!((stats.live(), stats.high_water_mark()), (2, 2));
+
+    drop(b);
+    let c = TinyIdAlloc::with_pool(TinyPool);
+    drop(a);
+
+    assert_eq!(stats.live(), 1);
+    assert_eq!(stats.high_water_mark(), 2);
+    assert_eq!(stats.fresh(), 2);
+    assert_eq!(stats.recycled(), 1);
+    assert_eq!(stats.remaining(), 253);
+
+    let mut c = c;
+    c.rekey_with_id_alloc(&mut TinyIdAlloc);
+    assert_eq!((stats.live(), stats.fresh()), (1, 3));
+
+    drop(c);
+    assert_eq!(stats.live(), 0);
+}
+
+#[test]
+#[cfg(feature = "stats")]
+fn stats_capacity() {
+    use pui::{runtime::IdAlloc, Scalar};
+
+    pui::make_global_id_alloc! {
+        type ThreeBytes(ThreeBytesId) = [u8; 3];
+    }
+
+    pui::make_global_id_alloc! {
+        type NonZero(NonZeroId) = core::num::NonZeroI8;
+    }
+
+    assert_eq!(ThreeBytes::stats().capacity(), (1 << 24) - 1);
+    assert_eq!(<ThreeBytes as IdAlloc>::stats().unwrap().capacity(), (1 << 24) - 1);
+    assert_eq!(NonZero::stats().capacity(), 254);
+    assert_eq!(<() as Scalar>::CAPACITY, 1);
+
+    for _ in 0..4 {
+        NonZero.alloc();
+    }
+
+    for _ in 0..250 {
+        core::mem::forget(NonZero::new());
+    }
+
+    assert_eq!(NonZero::stats().fresh(), 250);
+    assert_eq!(NonZero::stats().allocated(), 254);
+    assert_eq!(NonZero::stats().remaining(), 0);
+    assert!(NonZero::try_new().is_none());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn observer() {
+    use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+    static EVENTS: [AtomicUsize; 4] = [
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+    ];
+
+    fn events() -> [usize; 4] {
+        let mut events = [0; 4];
+        for (count, event) in events.iter_mut().zip(&EVENTS) {
+            *count = event.load(Relaxed);
+        }
+        events
+    }
+
+    struct Counter;
+
+    impl pui::runtime::IdAllocObserver<TinyId> for Counter {
+        fn on_alloc(_: &TinyId) { EVENTS[0].fetch_add(1, Relaxed); }
+
+        fn on_recycle(_: &TinyId) { EVENTS[1].fetch_add(1, Relaxed); }
+
+        fn on_release(_: &TinyId) { EVENTS[2].fetch_add(1, Relaxed); }
+
+        fn on_exhausted() { EVENTS[3].fetch_add(1, Relaxed); }
+    }
+
+    pui::make_global_id_alloc! {
+        type TinyIdAlloc(TinyId) = ();
+        observer = Counter;
+    }
+
+    pui::make_global_pool! {
+        one TinyPool(TinyId);
+    }
+
+    let a = TinyIdAlloc::with_pool(TinyPool);
+    assert_eq!(events(), [1, 0, 0, 0]);
+
+    assert!(TinyIdAlloc::try_new().is_none());
+    assert_eq!(events(), [1, 0, 0, 1]);
+
+    drop(a);
+    let _b = TinyIdAlloc::with_pool(TinyPool);
+    assert_eq!(events(), [1, 1, 1, 1]);
+}
@@ -27,6 +27,10 @@ make_global_pool! {
     one One(GlobalId);
 }
 
+make_global_pool! {
+    slots(2) Slots(GlobalId);
+}
+
 #[cfg(feature = "std")]
 make_global_pool! {
     thread_local one OneTl(GlobalId);
@@ -163,6 +167,32 @@ fn one() {
     }
 }
 
+#[test]
+fn slots() {
+    let (a, b, c);
+    {
+        let rt_a = Global::with_pool(Slots);
+        let rt_b = Global::with_pool(Slots);
+        let rt_c = Global::with_pool(Slots);
+        a = rt_a.handle();
+        b = rt_b.handle();
+        c = rt_c.handle();
+    }
+
+    {
+        let rt_a = Global::with_pool(Slots);
+        let rt_b = Global::with_pool(Slots);
+        let rt_c = Global::with_pool(Slots);
+        assert_ne!(rt_a.handle(), rt_b.handle());
+        // `a` is dropped last, so there is no slot left for it
+        assert!(rt_a.handle() == b || rt_a.handle() == c);
+        assert!(rt_b.handle() == b || rt_b.handle() == c);
+        assert_ne!(rt_c.handle(), a);
+        assert_ne!(rt_c.handle(), b);
+        assert_ne!(rt_c.handle(), c);
+    }
+}
+
 #[test]
 #[cfg(feature = "std")]
 fn one_tl() {
@@ -0,0 +1,44 @@
+use pui::scoped::{same_len, same_len3};
+
+#[test]
+fn same_len_zip() {
+    let a = [1, 2, 3, 4];
+    let b = [10, 20, 30, 40];
+
+    let sums = same_len(&a, &b, |ab| {
+        ab.indices()
+            .map(|i| {
+                let (a, b) = ab.get(i);
+                a + b
+            })
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(sums, Some(vec![11, 22, 33, 44]));
+
+    let last = same_len(&a, &b, |ab| {
+        let idx = ab.idx(3).unwrap();
+        assert!(ab.first().owns(&idx));
+        ab.get(idx)
+    });
+    assert_eq!(last, Some((&4, &40)));
+
+    let tail = same_len(&a, &b, |ab| {
+        let slice = ab.first();
+        let range = slice.span(slice.pos(2).unwrap(), slice.pos(4).unwrap());
+        ab.get_range(range)
+    });
+    assert_eq!(tail, Some((&a[2..], &b[2..])));
+}
+
+#[test]
+fn same_len_mismatch() {
+    let a = [1, 2, 3];
+    let b = [1, 2];
+
+    assert_eq!(same_len(&a, &b, |_| unreachable!()), None::<()>);
+    assert_eq!(same_len3(&a, &a, &b, |_| unreachable!()), None::<()>);
+    assert_eq!(
+        same_len3(&a, &a, &a, |abc| abc.len() + abc.second().len() + abc.third().len()),
+        Some(9)
+    );
+}
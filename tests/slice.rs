@@ -0,0 +1,285 @@
+use pui::{scoped::Scoped, slice::BrandedSlice};
+
+#[test]
+fn smoke() {
+    let values = [10, 20, 30];
+
+    Scoped::with(|ident| {
+        let slice = BrandedSlice::new(&values, ident);
+
+        assert_eq!(slice.len(), 3);
+        assert!(slice.idx(3).is_none());
+
+        let idx = slice.idx(1).unwrap();
+        assert_eq!(idx.get(), 1);
+        assert_eq!(slice[idx], 20);
+
+        assert_eq!(slice.indices().len(), 3);
+        assert_eq!(slice.indices().rev().map(|idx| slice[idx]).collect::<Vec<_>>(), [
+            30, 20, 10
+        ]);
+    })
+}
+
+#[test]
+fn runtime() {
+    let values = [1, 2, 3];
+    let a = BrandedSlice::new(&values, pui::runtime::Runtime::new());
+    let b = BrandedSlice::new(&values, pui::runtime::Runtime::new());
+
+    let idx = a.idx(2).unwrap();
+    assert!(a.owns(&idx));
+    assert!(!b.owns(&idx));
+    assert_eq!(a[idx], 3);
+}
+
+#[test]
+#[should_panic = "tried to use an index that is not owned by this slice"]
+fn runtime_foreign_index() {
+    let values = [1, 2, 3];
+    let a = BrandedSlice::new(&values, pui::runtime::Runtime::new());
+    let b = BrandedSlice::new(&values[..1], pui::runtime::Runtime::new());
+
+    let _ = b[a.idx(2).unwrap()];
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn idx_set() {
+    use pui::slice::IdxSet;
+
+    let values = [0; 130];
+
+    Scoped::with(|ident| {
+        let slice = BrandedSlice::new(&values, ident);
+        let mut set = IdxSet::new(&slice);
+
+        assert_eq!(set.capacity(), 130);
+        assert!(set.is_empty());
+
+        for idx in slice.indices().step_by(3) {
+            assert!(set.insert(idx));
+        }
+
+        assert_eq!(set.len(), 44);
+        assert!(!set.insert(slice.idx(129).unwrap()));
+        assert!(set.contains(slice.idx(66).unwrap()));
+        assert!(!set.contains(slice.idx(67).unwrap()));
+
+        assert!(set.remove(slice.idx(66).unwrap()));
+        assert!(!set.remove(slice.idx(66).unwrap()));
+
+        assert!(set
+            .iter()
+            .map(|idx| idx.get())
+            .eq((0..130).step_by(3).filter(|&i| i != 66)));
+
+        set.clear();
+        assert!(set.is_empty());
+    })
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[should_panic = "tried to use an index that is not owned by this set's slice"]
+fn idx_set_foreign_index() {
+    use pui::slice::IdxSet;
+
+    let values = [1, 2, 3];
+    let a = BrandedSlice::new(&values, pui::runtime::Runtime::new());
+    let b = BrandedSlice::new(&values[..1], pui::runtime::Runtime::new());
+
+    let mut set = IdxSet::new(&b);
+    set.insert(a.idx(2).unwrap());
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn idx_map() {
+    use pui::slice::IdxMap;
+
+    let values = ["a", "bb", "ccc"];
+
+    Scoped::with(|ident| {
+        let slice = BrandedSlice::new(&values, ident);
+        let mut map = IdxMap::new(&slice);
+
+        for idx in slice.indices() {
+            assert_eq!(map.insert(idx, slice[idx].len()), None);
+        }
+
+        let first = slice.idx(0).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.insert(first, 10), Some(1));
+        map[first] += 1;
+        assert_eq!(map[first], 11);
+
+        assert_eq!(map.remove(first), Some(11));
+        assert!(!map.contains_key(first));
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(map.iter().map(|(idx, _)| idx.get()).collect::<Vec<_>>(), [1, 2]);
+    })
+}
+
+#[test]
+fn positions_and_ranges() {
+    let values = [1, 2, 3, 4];
+
+    Scoped::with(|ident| {
+        let slice = BrandedSlice::new(&values, ident);
+
+        assert!(slice.pos(5).is_none());
+        let end = slice.pos(4).unwrap();
+        let start = pui::slice::Pos::from(slice.idx(1).unwrap());
+
+        let range = slice.span(start, end);
+        assert_eq!(range.get(), 1..4);
+        assert_eq!(range.len(), 3);
+        assert_eq!(slice[range], [2, 3, 4]);
+        assert_eq!(range.indices().map(|idx| slice[idx]).sum::<i32>(), 9);
+    })
+}
+
+#[test]
+fn sorted_slice() {
+    use pui::slice::SortedSlice;
+
+    let values = [1, 3, 3, 5, 8, 13];
+
+    Scoped::with(|ident| {
+        let sorted = SortedSlice::new(&values, ident).ok().unwrap();
+
+        assert_eq!(sorted.binary_search(&8).map(|idx| sorted[idx]), Ok(8));
+        assert_eq!(sorted.binary_search(&0).unwrap_err().get(), 0);
+        assert_eq!(sorted.binary_search(&20).unwrap_err().get(), 6);
+        assert_eq!(sorted.partition_point(|&x| x < 5).get(), 3);
+
+        assert_eq!(sorted[sorted.range(3..8)], [3, 3, 5]);
+        assert_eq!(sorted[sorted.range(3..=8)], [3, 3, 5, 8]);
+        assert_eq!(sorted[sorted.range(..)], values);
+        assert_eq!(sorted[sorted.range(10..)], [13]);
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = sorted.range(8..3);
+        assert!(reversed.is_empty());
+        assert!(sorted.range(100..).is_empty());
+
+        let first = sorted.binary_search_by_key(&1, |&x| x).unwrap();
+        assert_eq!(first.get(), 0);
+
+        // inconsistent comparators still give positions that are in bounds
+        let mut flip = false;
+        let pos = sorted.binary_search_by(|_| {
+            flip = !flip;
+            if flip {
+                core::cmp::Ordering::Less
+            } else {
+                core::cmp::Ordering::Greater
+            }
+        });
+        assert!(pos.map(|idx| idx.get()).unwrap_or_else(|pos| pos.get()) <= sorted.len());
+        assert!(sorted.partition_point(|&x| x % 2 == 1).get() <= sorted.len());
+    });
+
+    let unsorted = [2, 1];
+    let error = SortedSlice::new(&unsorted, pui::runtime::Runtime::new()).err().unwrap();
+    assert_eq!(error.as_slice(), unsorted);
+}
+
+#[test]
+fn sorted_slice_sort() {
+    let mut values = [5, 1, 4, 2];
+    let sorted = pui::slice::SortedSlice::sort(&mut values, pui::runtime::Runtime::new());
+    assert_eq!(sorted.as_slice(), [1, 2, 4, 5]);
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn sorted_slice_merge() {
+    use pui::slice::SortedSlice;
+
+    let a = SortedSlice::new(&[1, 4, 4, 9][..], pui::runtime::Runtime::new())
+        .ok()
+        .unwrap();
+    let b = SortedSlice::new_assume_sorted(&[0, 4, 10][..], pui::runtime::Runtime::new());
+
+    assert_eq!(a.merge(&b), [0, 1, 4, 4, 4, 9, 10]);
+}
+
+#[test]
+#[should_panic = "tried to use a range that is not owned by this slice"]
+fn runtime_foreign_range() {
+    use pui::slice::SortedSlice;
+
+    let values = [1, 2, 3];
+    let a = SortedSlice::new(&values, pui::runtime::Runtime::new()).ok().unwrap();
+    let b = BrandedSlice::new(&values[..1], pui::runtime::Runtime::new());
+
+    let _ = &b[a.range(2..)];
+}
+
+#[test]
+fn split_into() {
+    use pui::slice::BrandedSliceMut;
+
+    let mut values: Vec<usize> = (0..10).collect();
+
+    Scoped::with(|ident| {
+        let mut slice = BrandedSliceMut::new(&mut values, ident);
+
+        let chunks = slice.split_into::<3>();
+        assert_eq!(chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(), [4, 3, 3]);
+        assert_eq!(chunks.iter().map(|chunk| chunk.offset()).collect::<Vec<_>>(), [0, 4, 7]);
+
+        let firsts: Vec<_> = std::thread::scope(|scope| {
+            let mut threads = Vec::new();
+
+            for chunk in chunks {
+                threads.push(scope.spawn(move || {
+                    chunk.with(|mut sub| {
+                        for idx in sub.indices() {
+                            sub[idx] *= 2;
+                        }
+                        sub.to_parent(sub.idx(0).unwrap())
+                    })
+                }));
+            }
+
+            threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+        });
+
+        assert_eq!(firsts.into_iter().map(|idx| slice[idx]).collect::<Vec<_>>(), [0, 8, 14]);
+    });
+
+    assert_eq!(values, (0..20).step_by(2).collect::<Vec<_>>());
+}
+
+#[test]
+fn chunks() {
+    use pui::slice::BrandedSliceMut;
+
+    let mut values = [1, 2, 3, 4, 5];
+    let mut slice = BrandedSliceMut::new(&mut values, pui::runtime::Runtime::new());
+
+    let lasts: Vec<_> = slice
+        .chunks(2)
+        .map(|chunk| chunk.with(|sub| sub.to_parent(sub.idx(sub.len() - 1).unwrap())))
+        .collect();
+
+    assert_eq!(lasts.iter().map(|idx| idx.get()).collect::<Vec<_>>(), [1, 3, 4]);
+    assert!(lasts.iter().all(|idx| slice.owns(idx)));
+
+    for idx in lasts {
+        slice[idx] = 0;
+    }
+
+    assert_eq!(values, [1, 0, 3, 0, 0]);
+}
+
+#[test]
+#[should_panic = "tried to split a slice into 0 chunks"]
+fn split_into_zero() {
+    let mut values = [1];
+    Scoped::with(|ident| {
+        let _ = pui::slice::BrandedSliceMut::new(&mut values, ident).split_into::<0>();
+    })
+}
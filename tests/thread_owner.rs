@@ -0,0 +1,45 @@
+#![cfg(feature = "std")]
+
+use pui::{cell::IdCell, Identifier};
+
+#[test]
+fn smoke() {
+    let a = IdCell::new(pui::thread_owner(), 1);
+    let b = IdCell::new(pui::thread_owner(), 2);
+
+    pui::with_thread_owner(|owner| {
+        assert!(owner.owns(&pui::thread_owner()));
+        let (a, b) = a.borrow_mut2(&b, owner);
+        std::mem::swap(a, b);
+    });
+
+    pui::with_thread_owner(|owner| assert_eq!((*a.borrow(owner), *b.borrow(owner)), (2, 1)));
+}
+
+#[test]
+fn reentrant() {
+    pui::with_thread_owner(|_| {
+        assert!(pui::try_with_thread_owner(|_| ()).is_none());
+    });
+
+    assert!(pui::try_with_thread_owner(|_| ()).is_some());
+}
+
+#[test]
+#[should_panic = "tried to use the thread owner while it is already in use"]
+fn reentrant_panics() { pui::with_thread_owner(|_| pui::with_thread_owner(|_| ())) }
+
+#[test]
+fn per_thread() {
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            std::thread::spawn(move || {
+                let cell = IdCell::new(pui::thread_owner(), i);
+                pui::with_thread_owner(|owner| *cell.borrow_mut(owner) * 10)
+            })
+        })
+        .collect();
+
+    let results: Vec<_> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+    assert_eq!(results, [0, 10, 20, 30]);
+}
@@ -67,3 +67,39 @@ fn multi_threaded() {
         wait.wait();
     });
 }
+
+#[test]
+fn observer() {
+    use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+    static ACQUIRED: AtomicUsize = AtomicUsize::new(0);
+    static RELEASED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counter;
+
+    impl<T> pui::typeid::TypeIdObserver<T> for Counter {
+        fn on_acquire() { ACQUIRED.fetch_add(1, Relaxed); }
+
+        fn on_release() { RELEASED.fetch_add(1, Relaxed); }
+    }
+
+    pui::make_typeid! {
+        type Resettable;
+        observer = Counter;
+    }
+
+    pui::make_typeid! {
+        once type Once;
+        observer = Counter;
+    }
+
+    let a = Resettable::new();
+    assert!(Resettable::try_new().is_none());
+    drop(a);
+    let _b = Resettable::try_new().unwrap();
+    assert_eq!((ACQUIRED.load(Relaxed), RELEASED.load(Relaxed)), (2, 1));
+
+    let _c = Once::new();
+    assert!(Once::try_new().is_none());
+    assert_eq!((ACQUIRED.load(Relaxed), RELEASED.load(Relaxed)), (3, 1));
+}
@@ -0,0 +1,72 @@
+use pui::{scoped::Scoped, utf8::BrandedStr};
+
+#[test]
+fn boundaries() {
+    Scoped::with(|ident| {
+        let text = BrandedStr::new("aé😀", ident);
+
+        assert_eq!(text.len(), 7);
+        assert!(text.boundary(2).is_none());
+        assert!(text.boundary(8).is_none());
+
+        let offsets: Vec<_> = text.boundaries().map(|b| b.get()).collect();
+        assert_eq!(offsets, [0, 1, 3, 7]);
+
+        let e = text.boundary(1).unwrap();
+        assert_eq!(text.char_at(e), Some('é'));
+        assert_eq!(text.char_at(text.end()), None);
+
+        let smile = text.next_boundary(e).unwrap();
+        assert_eq!(smile.get(), 3);
+        assert_eq!(text.prev_boundary(smile), Some(e));
+        assert_eq!(text.prev_boundary(text.start()), None);
+        assert_eq!(text.next_boundary(text.end()), None);
+
+        assert_eq!(&text[e..smile], "é");
+        assert_eq!(&text[smile..], "😀");
+        assert_eq!(&text[..e], "a");
+        assert_eq!(text.split_at(smile), ("aé", "😀"));
+    })
+}
+
+#[test]
+fn search() {
+    Scoped::with(|ident| {
+        let text = BrandedStr::new("ünï, cöde, ünï", ident);
+
+        let first = text.find("ünï").unwrap();
+        let last = text.rfind("ünï").unwrap();
+        assert_eq!(first.get(), 0);
+        assert_eq!(&text[last..], "ünï");
+
+        let comma = text.position(|c| c == ',').unwrap();
+        assert_eq!(&text[..comma], "ünï");
+
+        let words: Vec<_> = text
+            .match_indices(", ")
+            .map(|(start, end)| (start.get(), end.get()))
+            .collect();
+        assert_eq!(words, [(5, 7), (12, 14)]);
+
+        let chars: String = text.char_indices().map(|(_, c)| c).collect();
+        assert_eq!(chars, text.as_str());
+    })
+}
+
+#[test]
+#[should_panic = "slice index starts at 1 but ends at 0"]
+fn reversed_range() {
+    Scoped::with(|ident| {
+        let text = BrandedStr::new("ab", ident);
+        let _ = &text[text.boundary(1).unwrap()..text.start()];
+    })
+}
+
+#[test]
+#[should_panic = "tried to use a char boundary that is not owned by this string"]
+fn runtime_foreign_boundary() {
+    let a = BrandedStr::new("abc", pui::runtime::Runtime::new());
+    let b = BrandedStr::new("abc", pui::runtime::Runtime::new());
+
+    let _ = &b[a.start()..];
+}